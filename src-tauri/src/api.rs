@@ -0,0 +1,224 @@
+//! Optional HTTP API for scripts and monitoring systems, serving the same data
+//! as the `list_ports` and `kill_process` commands:
+//!
+//! - `GET /ports` returns the `list_ports` listing as JSON.
+//! - `GET /metrics` returns it as Prometheus exposition text, as `get_metrics` does.
+//! - `POST /kill` with `{ "pid": 123 }` kills the PID and returns the message
+//!   `kill_process` would, or its error with a 4xx/5xx status.
+//!
+//! Every request needs `Authorization: Bearer <token>`, with the token written
+//! to `~/.process-monitor/api_token` when the server starts. The server only
+//! listens on loopback.
+
+use super::{export, kill_and_record, sorted_ports, AppState, DynProvider, KillError};
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tauri::{AppHandle, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const JSON: &str = "application/json";
+
+/// The exposition format version Prometheus negotiates for plain-text scrapes.
+const PROMETHEUS_TEXT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Where the token goes, relative to the home directory.
+const TOKEN_PATH: &str = ".process-monitor/api_token";
+
+/// Largest `POST /kill` body read; `{ "pid": 4294967295 }` needs a fraction of it.
+const MAX_KILL_BODY_BYTES: u64 = 4096;
+
+/// Managed state holding the running server, if any.
+#[derive(Default)]
+pub(crate) struct ApiServerState {
+    server: Mutex<Option<RunningServer>>,
+}
+
+struct RunningServer {
+    server: Arc<Server>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Deserialize)]
+struct KillRequest {
+    pid: u32,
+}
+
+impl ApiServerState {
+    /// Starts serving on `127.0.0.1:<port>` with a fresh token, replacing any
+    /// server that is already running.
+    pub(crate) fn start(&self, app: AppHandle, port: u16) -> Result<(), String> {
+        // Stop first so a restart on the same port can bind it again
+        self.stop();
+
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| format!("Failed to start API server on port {}: {}", port, e))?;
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let token_path = app
+            .path()
+            .home_dir()
+            .map_err(|e| format!("Failed to find the home directory: {}", e))?
+            .join(TOKEN_PATH);
+        write_token(&token_path, &token).map_err(|e| format!("Failed to write {}: {}", token_path.display(), e))?;
+        tracing::debug!("API server listening on port {}, token in {}", port, token_path.display());
+
+        let server = Arc::new(server);
+        let handle = {
+            let server = Arc::clone(&server);
+            let authorization = format!("Bearer {}", token);
+            thread::spawn(move || {
+                // Ends once `stop` unblocks the server
+                for request in server.incoming_requests() {
+                    handle_request(&app, &authorization, request);
+                }
+            })
+        };
+
+        *self.server.lock().unwrap_or_else(|e| e.into_inner()) = Some(RunningServer { server, handle });
+        Ok(())
+    }
+
+    /// Stops the server and waits for it to finish the request in progress.
+    /// Returns whether one was running.
+    pub(crate) fn stop(&self) -> bool {
+        let running = self.server.lock().unwrap_or_else(|e| e.into_inner()).take();
+        match running {
+            Some(running) => {
+                running.server.unblock();
+                let _ = running.handle.join();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Writes `token`, readable by the current user only. The modes are set on
+/// every write, since a file or directory left from an earlier run keeps the
+/// mode it was created with.
+fn write_token(path: &Path, token: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        #[cfg(not(target_os = "windows"))]
+        fs::set_permissions(parent, std::os::unix::fs::PermissionsExt::from_mode(0o700))?;
+    }
+    let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
+    #[cfg(not(target_os = "windows"))]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(token.as_bytes())
+}
+
+/// Compares in time independent of where `a` and `b` first differ, so the
+/// token can't be guessed a byte at a time from response times.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn handle_request(app: &AppHandle, authorization: &str, mut request: Request) {
+    tracing::debug!("API request {} {}", request.method(), request.url());
+
+    let authorized = request.headers().iter().any(|header| {
+        header.field.equiv("Authorization") && constant_time_eq(header.value.as_bytes(), authorization.as_bytes())
+    });
+    let (status, content_type, body) = if !authorized {
+        (401, JSON, error_body("Missing or invalid bearer token"))
+    } else {
+        let path = request.url().split('?').next().unwrap_or_default();
+        let listing = || {
+            sorted_ports(&app.state::<AppState>(), &**app.state::<DynProvider>(), None).map(|listing| listing.ports)
+        };
+        match (request.method(), path) {
+            (Method::Get, "/ports") => match listing() {
+                Ok(ports) => (200, JSON, serde_json::to_string(&ports).unwrap_or_default()),
+                Err(e) => (500, JSON, serde_json::to_string(&e).unwrap_or_default()),
+            },
+            (Method::Get, "/metrics") => match listing() {
+                Ok(ports) => (200, PROMETHEUS_TEXT, export::prometheus(&ports)),
+                Err(e) => (500, JSON, serde_json::to_string(&e).unwrap_or_default()),
+            },
+            (Method::Post, "/kill") => {
+                let (status, body) = kill(app, &mut request);
+                (status, JSON, body)
+            }
+            (_, "/ports" | "/metrics" | "/kill") => (405, JSON, error_body("Method not allowed")),
+            _ => (404, JSON, error_body("Not found")),
+        }
+    };
+
+    let content_type = Header::from_bytes("Content-Type", content_type).expect("static header is valid");
+    let response = Response::from_string(body).with_status_code(status).with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        tracing::warn!("Failed to send API response: {}", e);
+    }
+}
+
+/// `POST /kill`: what `kill_process` does, with the error's kind mapped to a status.
+fn kill(app: &AppHandle, request: &mut Request) -> (u16, String) {
+    let mut body = String::new();
+    // One byte past the limit tells a body that is too long from one that fits exactly
+    if let Err(e) = request.as_reader().take(MAX_KILL_BODY_BYTES + 1).read_to_string(&mut body) {
+        return (400, error_body(&format!("Failed to read request body: {}", e)));
+    }
+    if body.len() as u64 > MAX_KILL_BODY_BYTES {
+        return (413, error_body(&format!("Request body exceeds {} bytes", MAX_KILL_BODY_BYTES)));
+    }
+    let kill_request: KillRequest = match serde_json::from_str(&body) {
+        Ok(kill_request) => kill_request,
+        Err(e) => return (400, error_body(&format!("Expected {{ \"pid\": <number> }}: {}", e))),
+    };
+
+    match kill_and_record(&app.state(), &**app.state::<DynProvider>(), kill_request.pid) {
+        Ok(message) => (200, serde_json::json!({ "message": message }).to_string()),
+        Err(e) => {
+            let status = match e {
+                KillError::ProcessNotFound(_) | KillError::RaceCondition(_) => 404,
+                KillError::PermissionDenied(_) | KillError::PolicyViolation { .. } => 403,
+                KillError::CommandFailed { .. } | KillError::SpawnError(_) => 500,
+            };
+            (status, serde_json::to_string(&e).unwrap_or_default())
+        }
+    }
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_time_eq_compares_whole_tokens() {
+        assert!(constant_time_eq(b"Bearer abc", b"Bearer abc"));
+        assert!(!constant_time_eq(b"Bearer abd", b"Bearer abc"));
+        assert!(!constant_time_eq(b"Bearer ab", b"Bearer abc"));
+        assert!(!constant_time_eq(b"", b"Bearer abc"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn write_token_tightens_an_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("process-monitor-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(TOKEN_PATH);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::set_permissions(path.parent().unwrap(), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_token(&path, "new").unwrap();
+        let file_mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let dir_mode = fs::metadata(path.parent().unwrap()).unwrap().permissions().mode() & 0o777;
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(file_mode, 0o600);
+        assert_eq!(dir_mode, 0o700);
+        assert_eq!(contents, "new");
+    }
+}
@@ -0,0 +1,183 @@
+//! Append-only NDJSON log of kill actions, so it's possible to tell afterwards
+//! who killed which process and when.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The log is moved aside to `<path>.1` once it grows past this size.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// One line of the audit log. Fields added after the first release default
+/// when reading older lines.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct KillLogEntry {
+    /// UTC, RFC 3339 (e.g. `"2025-01-31T12:00:00Z"`).
+    timestamp: String,
+    /// The same instant as `timestamp`, for sorting and arithmetic.
+    #[serde(default)]
+    timestamp_unix_secs: u64,
+    invoking_user: String,
+    pid: u32,
+    signal: i32,
+    process_name: String,
+    /// Full command line, or empty if it couldn't be read.
+    #[serde(default)]
+    command: String,
+    success: bool,
+    /// Why the signal failed; None when `success` is set.
+    #[serde(default)]
+    error: Option<String>,
+    /// Set when the signal went to the whole process group `pid` leads.
+    #[serde(default)]
+    process_group: Option<u32>,
+}
+
+impl KillLogEntry {
+    /// An entry for `signal` sent to `pid` just now by the user running the app,
+    /// which failed with `error` if one is given.
+    pub(crate) fn new(pid: u32, signal: i32, process_name: String, command: String, error: Option<String>) -> Self {
+        let now = SystemTime::now();
+        KillLogEntry {
+            timestamp: utc_timestamp(now),
+            timestamp_unix_secs: now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            invoking_user: invoking_user(),
+            pid,
+            signal,
+            process_name,
+            command,
+            success: error.is_none(),
+            error,
+            process_group: None,
+        }
+    }
+
+    /// An entry for `signal` sent to every member of process group `pgid`,
+    /// named after the group leader.
+    #[cfg(not(target_os = "windows"))]
+    pub(crate) fn group(pgid: u32, signal: i32, process_name: String, command: String, error: Option<String>) -> Self {
+        KillLogEntry {
+            process_group: Some(pgid),
+            ..KillLogEntry::new(pgid, signal, process_name, command, error)
+        }
+    }
+}
+
+pub(crate) struct AuditLog {
+    path: PathBuf,
+    /// Serializes appends and rotation between concurrent kill commands.
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        AuditLog {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Appends `entry`, rotating the file first if it is over `MAX_LOG_BYTES`.
+    pub(crate) fn record(&self, entry: &KillLogEntry) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        if fs::metadata(&self.path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+            fs::rename(&self.path, self.rotated_path())
+                .map_err(|e| format!("Failed to rotate audit log: {}", e))?;
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create audit log directory: {}", e))?;
+        }
+
+        let mut line = serde_json::to_string(entry).map_err(|e| format!("Failed to encode audit entry: {}", e))?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write audit log: {}", e))
+    }
+
+    /// Returns the last `limit` entries, newest first, including the rotated file.
+    /// Lines that don't parse are skipped.
+    pub(crate) fn last_entries(&self, limit: usize) -> Result<Vec<KillLogEntry>, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut entries = Vec::new();
+        for path in [self.rotated_path(), self.path.clone()] {
+            entries.extend(read_entries(&path)?);
+        }
+        let skip = entries.len().saturating_sub(limit);
+        let mut entries = entries.split_off(skip);
+        entries.reverse();
+        Ok(entries)
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        rotated.into()
+    }
+}
+
+/// Reads every parseable entry in `path`; a missing file has none.
+fn read_entries(path: &Path) -> Result<Vec<KillLogEntry>, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read audit log: {}", e)),
+    }
+}
+
+fn invoking_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// Formats `time` as `YYYY-MM-DDTHH:MM:SSZ` without a date library, using the
+/// days-to-civil conversion from Howard Hinnant's date algorithms.
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn utc_timestamp_handles_epoch_leap_days_and_centuries() {
+        let at = |secs| utc_timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_709_251_199), "2024-02-29T23:59:59Z");
+        assert_eq!(at(4_102_444_800), "2100-01-01T00:00:00Z");
+    }
+}
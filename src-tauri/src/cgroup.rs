@@ -0,0 +1,85 @@
+//! Resource usage of cgroup v2 groups, read from the unified hierarchy under
+//! `/sys/fs/cgroup`. Interface files are only present for the controllers
+//! enabled on a group, so each figure is optional.
+
+use super::CgroupStats;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Where the unified hierarchy is mounted: `/sys/fs/cgroup` on pure v2 systems,
+/// `/sys/fs/cgroup/unified` on hybrid ones that still mount v1 controllers.
+fn unified_root() -> Option<PathBuf> {
+    ["/sys/fs/cgroup", "/sys/fs/cgroup/unified"]
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|root| root.join("cgroup.controllers").is_file())
+}
+
+/// Reads `memory.current`, `memory.max`, `cpu.stat` and `io.stat` of the group
+/// at `cgroup_path` (as in `PidInfo.cgroup_path`, e.g. `/system.slice/nginx.service`).
+pub(crate) fn stats(cgroup_path: &str) -> Result<CgroupStats, String> {
+    // Only plain path segments, so the path can't climb out of the hierarchy
+    let relative = Path::new(cgroup_path.trim_start_matches('/'));
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid cgroup path {:?}", cgroup_path));
+    }
+    let root = unified_root().ok_or("No cgroup v2 hierarchy is mounted")?;
+    let dir = root.join(relative);
+    if !dir.is_dir() {
+        return Err(format!("cgroup {} does not exist", cgroup_path));
+    }
+
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    // "key value" lines, e.g. "usage_usec 123456"
+    let cpu_stat = read("cpu.stat").unwrap_or_default();
+    let cpu = |key: &str| {
+        cpu_stat.lines().find_map(|line| {
+            let (name, value) = line.split_once(' ')?;
+            (name == key).then(|| value.trim().parse().ok()).flatten()
+        })
+    };
+    let (io_read_bytes, io_write_bytes) = read("io.stat").map(|io| io_totals(&io)).unzip();
+
+    Ok(CgroupStats {
+        cgroup_path: cgroup_path.to_string(),
+        memory_current_bytes: read("memory.current").and_then(|value| value.trim().parse().ok()),
+        // "max" means no limit
+        memory_max_bytes: read("memory.max").and_then(|value| value.trim().parse().ok()),
+        cpu_usage_usec: cpu("usage_usec"),
+        cpu_user_usec: cpu("user_usec"),
+        cpu_system_usec: cpu("system_usec"),
+        cpu_nr_throttled: cpu("nr_throttled"),
+        cpu_throttled_usec: cpu("throttled_usec"),
+        io_read_bytes,
+        io_write_bytes,
+    })
+}
+
+/// Sums `rbytes` and `wbytes` over the devices in `io.stat`, whose lines look
+/// like `8:0 rbytes=1459200 wbytes=314773504 rios=192 wios=353 dbytes=0 dios=0`.
+fn io_totals(io_stat: &str) -> (u64, u64) {
+    let mut totals = (0, 0);
+    for pair in io_stat.split_whitespace() {
+        match pair.split_once('=') {
+            Some(("rbytes", value)) => totals.0 += value.parse::<u64>().unwrap_or(0),
+            Some(("wbytes", value)) => totals.1 += value.parse::<u64>().unwrap_or(0),
+            _ => {}
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_totals_sums_every_device() {
+        let io_stat = "\
+8:0 rbytes=1459200 wbytes=314773504 rios=192 wios=353 dbytes=0 dios=0
+259:0 rbytes=40960 wbytes=4096 rios=10 wios=1 dbytes=0 dios=0
+";
+        assert_eq!(io_totals(io_stat), (1_500_160, 314_777_600));
+        assert_eq!(io_totals(""), (0, 0));
+    }
+}
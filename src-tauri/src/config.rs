@@ -0,0 +1,286 @@
+//! Settings kept across restarts in `config.toml` in the app config directory.
+//! Some are applied to the backend: the listing cache TTL, the kill policy and,
+//! on Unix, the `lsof`/`ps` paths and timeouts. The rest (monitor intervals,
+//! the default signal, the export path) are stored for the frontend to read
+//! back with `load_config`. Logging is only set up from the file at startup.
+//! Missing sections and keys take their defaults.
+
+use super::{state, validate_signal, AppState, KillPolicy};
+#[cfg(not(target_os = "windows"))]
+use super::tools;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File name of the settings inside the app config directory.
+pub(crate) const CONFIG_FILE: &str = "config.toml";
+
+/// Levels `[logging] level` accepts, as `RUST_LOG` spells them.
+const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    monitoring: MonitoringConfig,
+    kill: KillConfig,
+    paths: PathsConfig,
+    pub(crate) logging: LoggingConfig,
+    export: ExportConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+struct MonitoringConfig {
+    /// For `start_monitor`.
+    monitor_interval_ms: u64,
+    /// For `watch_ports`, with `max_watch_interval_ms` as its backoff cap.
+    watch_interval_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_watch_interval_ms: Option<u64>,
+    cache_ttl_ms: u64,
+    /// Unset keeps the built-in timeouts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lsof_timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ps_timeout_ms: Option<u64>,
+}
+
+impl Default for MonitoringConfig {
+    fn default() -> Self {
+        MonitoringConfig {
+            monitor_interval_ms: 2000,
+            watch_interval_ms: 2000,
+            max_watch_interval_ms: None,
+            cache_ttl_ms: state::DEFAULT_CACHE_TTL_MS,
+            lsof_timeout_ms: None,
+            ps_timeout_ms: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+struct KillConfig {
+    protected_names: Vec<String>,
+    protected_users: Vec<String>,
+    /// The signal the frontend offers first.
+    default_signal: i32,
+}
+
+impl Default for KillConfig {
+    fn default() -> Self {
+        let policy = KillPolicy::default();
+        KillConfig {
+            protected_names: policy.protected_names,
+            protected_users: policy.protected_users,
+            default_signal: 15,
+        }
+    }
+}
+
+/// Bare names are looked up on PATH. Ignored on Windows.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+struct PathsConfig {
+    lsof: String,
+    ps: String,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        PathsConfig {
+            lsof: "lsof".to_string(),
+            ps: "ps".to_string(),
+        }
+    }
+}
+
+/// Needs the `tracing-subscriber` feature, and `RUST_LOG` still wins over `level`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct LoggingConfig {
+    pub(crate) level: String,
+    /// Log lines are appended here instead of going to stderr.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) file: Option<PathBuf>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        let level = if cfg!(debug_assertions) { "debug" } else { "info" };
+        LoggingConfig {
+            level: level.to_string(),
+            file: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+struct ExportConfig {
+    /// Where the frontend suggests saving `export_to_json` and `export_to_csv` files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Checks every setting the way the matching `configure_*` command would,
+    /// then applies them all, so an invalid file changes nothing.
+    pub(crate) fn apply(&self, state: &AppState) -> Result<(), String> {
+        let monitoring = &self.monitoring;
+        if monitoring.monitor_interval_ms == 0 || monitoring.watch_interval_ms == 0 {
+            return Err("Monitor and watch intervals must be greater than 0 ms".to_string());
+        }
+        if monitoring.max_watch_interval_ms.is_some_and(|max| max < monitoring.watch_interval_ms) {
+            return Err("Maximum watch interval must be at least the watch interval".to_string());
+        }
+        if monitoring.lsof_timeout_ms == Some(0) || monitoring.ps_timeout_ms == Some(0) {
+            return Err("Timeouts must be greater than 0".to_string());
+        }
+        validate_signal(self.kill.default_signal)?;
+        let policy = KillPolicy {
+            protected_names: self.kill.protected_names.clone(),
+            protected_users: self.kill.protected_users.clone(),
+        };
+        policy.validate()?;
+        if !LOG_LEVELS.contains(&self.logging.level.as_str()) {
+            return Err(format!("Log level must be one of {}", LOG_LEVELS.join(", ")));
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let defaults = PathsConfig::default();
+            // The bare defaults are accepted uninstalled, like the built-in ones
+            let check = |path: &str, default: &str| {
+                if path == default {
+                    Ok(PathBuf::from(path))
+                } else {
+                    tools::check_executable(path)
+                }
+            };
+            let lsof = check(&self.paths.lsof, &defaults.lsof)?;
+            let ps = check(&self.paths.ps, &defaults.ps)?;
+            tools::configure(lsof, ps);
+            tools::configure_timeouts(
+                monitoring.lsof_timeout_ms.unwrap_or(tools::DEFAULT_LSOF_TIMEOUT_MS),
+                monitoring.ps_timeout_ms.unwrap_or(tools::DEFAULT_PS_TIMEOUT_MS),
+            );
+        }
+        state.set_cache_ttl_ms(monitoring.cache_ttl_ms);
+        state.set_kill_policy(policy);
+        Ok(())
+    }
+
+    /// Copies in the settings the backend holds, which commands such as
+    /// `set_kill_policy` may have changed since this config was applied.
+    fn refresh(&mut self, state: &AppState) {
+        #[cfg(not(target_os = "windows"))]
+        {
+            let (lsof, ps) = tools::paths();
+            self.paths.lsof = lsof.to_string_lossy().into_owned();
+            self.paths.ps = ps.to_string_lossy().into_owned();
+            self.monitoring.lsof_timeout_ms = Some(tools::lsof_timeout_ms());
+            self.monitoring.ps_timeout_ms = Some(tools::ps_timeout_ms());
+        }
+        self.monitoring.cache_ttl_ms = state.cache_ttl_ms();
+        let policy = state.kill_policy();
+        self.kill.protected_names = policy.protected_names;
+        self.kill.protected_users = policy.protected_users;
+    }
+}
+
+/// Reads the config at `path`. A missing file gives the defaults.
+pub(crate) fn read(path: &Path) -> Result<Config, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    toml::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write(path: &Path, config: &Config) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let contents = toml::to_string_pretty(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    fs::write(path, contents).map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+}
+
+/// Managed state holding where the config lives and the config last applied,
+/// whose frontend-only settings are the ones `save_config` writes back.
+pub(crate) struct ConfigState {
+    path: PathBuf,
+    config: Mutex<Config>,
+}
+
+impl ConfigState {
+    pub(crate) fn new(path: PathBuf, config: Config) -> Self {
+        ConfigState {
+            path,
+            config: Mutex::new(config),
+        }
+    }
+
+    /// Reads the file again and applies it.
+    pub(crate) fn load(&self, state: &AppState) -> Result<Config, String> {
+        let config = read(&self.path)?;
+        config.apply(state)?;
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config.clone();
+        Ok(config)
+    }
+
+    /// Writes the settings in effect to the file.
+    pub(crate) fn save(&self, state: &AppState) -> Result<(), String> {
+        let mut config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+        config.refresh(state);
+        write(&self.path, &config)
+    }
+
+    /// Applies the defaults and writes them to the file.
+    pub(crate) fn reset(&self, state: &AppState) -> Result<(), String> {
+        let config = Config::default();
+        config.apply(state)?;
+        write(&self.path, &config)?;
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_sections_and_keys_take_their_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+[monitoring]
+cache_ttl_ms = 500
+
+[kill]
+protected_users = ["postgres"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.monitoring.cache_ttl_ms, 500);
+        assert_eq!(config.monitoring.monitor_interval_ms, MonitoringConfig::default().monitor_interval_ms);
+        assert_eq!(config.kill.protected_users, ["postgres"]);
+        assert_eq!(config.kill.protected_names, KillConfig::default().protected_names);
+        assert_eq!(config.paths, PathsConfig::default());
+    }
+
+    #[test]
+    fn an_invalid_config_changes_nothing() {
+        let (state, _dir) = AppState::for_tests();
+        let mut config = Config::default();
+        config.monitoring.cache_ttl_ms = 500;
+        config.logging.level = "verbose".to_string();
+
+        assert!(config.apply(&state).is_err());
+        assert_eq!(state.cache_ttl_ms(), state::DEFAULT_CACHE_TTL_MS);
+    }
+}
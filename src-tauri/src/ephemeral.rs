@@ -0,0 +1,55 @@
+//! The OS's ephemeral port range: ports handed out for the local end of outgoing
+//! connections, which are transient and rarely worth watching.
+
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::sync::LazyLock;
+
+/// The IANA suggested range, used where the OS range can't be read.
+const IANA_RANGE: (u16, u16) = (49152, 65535);
+
+static RANGE: LazyLock<(u16, u16)> = LazyLock::new(|| {
+    let range = read_range().unwrap_or(IANA_RANGE);
+    tracing::debug!("Ephemeral port range is {}-{}", range.0, range.1);
+    range
+});
+
+/// `/proc/sys/net/ipv4/ip_local_port_range` holds both bounds, e.g. `"32768\t60999"`.
+#[cfg(target_os = "linux")]
+fn read_range() -> Option<(u16, u16)> {
+    parse_bounds(&fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range").ok()?)
+}
+
+/// `sysctl -n` prints the two values one per line.
+#[cfg(target_os = "macos")]
+fn read_range() -> Option<(u16, u16)> {
+    let output = Command::new("sysctl")
+        .args(["-n", "net.inet.ip.portrange.first", "net.inet.ip.portrange.last"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    parse_bounds(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Windows defaults to the IANA range, which is what the fallback gives.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn read_range() -> Option<(u16, u16)> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn parse_bounds(text: &str) -> Option<(u16, u16)> {
+    let mut bounds = text.split_whitespace().map(str::parse::<u16>);
+    match (bounds.next()?.ok()?, bounds.next()?.ok()?) {
+        (first, last) if first <= last => Some((first, last)),
+        _ => None,
+    }
+}
+
+/// Whether `port` falls inside the ephemeral range.
+pub(crate) fn contains(port: u16) -> bool {
+    let (first, last) = *RANGE;
+    (first..=last).contains(&port)
+}
@@ -0,0 +1,211 @@
+//! Structured errors for commands whose failures the frontend needs to tell
+//! apart. Each serializes as `{ kind, message, ... }`, where `message` is the
+//! same text the command used to return as a plain string.
+
+use super::SignalError;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::fmt;
+#[cfg(not(target_os = "windows"))]
+use std::io;
+
+/// Why `kill_process` could not kill a process.
+#[derive(Debug)]
+pub(crate) enum KillError {
+    /// The process does not exist (or already exited).
+    ProcessNotFound(u32),
+    /// The process exists but belongs to someone we can't signal.
+    PermissionDenied(u32),
+    /// The process passed the existence check but was gone by the time SIGKILL was sent.
+    RaceCondition(u32),
+    /// The kill tool ran but failed for another reason.
+    CommandFailed { pid: u32, stderr: String },
+    /// The kill tool could not be run at all.
+    SpawnError(String),
+    /// The kill policy protects the process, so no signal was sent.
+    PolicyViolation { pid: u32, reason: String },
+}
+
+impl KillError {
+    /// Classifies a failed delivery to `pid` from the tool's error message.
+    pub(crate) fn from_signal_error(pid: u32, error: SignalError) -> Self {
+        let stderr = match error {
+            SignalError::Spawn(message) => return KillError::SpawnError(message),
+            SignalError::Failed(stderr) => stderr,
+        };
+
+        // kill: "No such process" / "Operation not permitted";
+        // taskkill: "... not found." / "Access is denied."
+        let lower = stderr.to_lowercase();
+        if lower.contains("no such process") || lower.contains("not found") {
+            KillError::ProcessNotFound(pid)
+        } else if lower.contains("not permitted") || lower.contains("access is denied") {
+            KillError::PermissionDenied(pid)
+        } else {
+            KillError::CommandFailed { pid, stderr }
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            KillError::ProcessNotFound(_) => "processNotFound",
+            KillError::PermissionDenied(_) => "permissionDenied",
+            KillError::RaceCondition(_) => "raceCondition",
+            KillError::CommandFailed { .. } => "commandFailed",
+            KillError::SpawnError(_) => "spawnError",
+            KillError::PolicyViolation { .. } => "policyViolation",
+        }
+    }
+
+    fn pid(&self) -> Option<u32> {
+        match self {
+            KillError::ProcessNotFound(pid)
+            | KillError::PermissionDenied(pid)
+            | KillError::RaceCondition(pid)
+            | KillError::CommandFailed { pid, .. }
+            | KillError::PolicyViolation { pid, .. } => Some(*pid),
+            KillError::SpawnError(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for KillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillError::ProcessNotFound(pid) => write!(f, "Failed to kill process {}: No such process", pid),
+            KillError::PermissionDenied(pid) => write!(f, "Failed to kill process {}: Operation not permitted", pid),
+            KillError::RaceCondition(pid) => write!(f, "Process {} exited before it could be killed", pid),
+            KillError::CommandFailed { pid, stderr } => write!(f, "Failed to kill process {}: {}", pid, stderr),
+            KillError::SpawnError(message) => f.write_str(message),
+            KillError::PolicyViolation { pid, reason } => write!(f, "Refusing to signal process {}: {}", pid, reason),
+        }
+    }
+}
+
+impl std::error::Error for KillError {}
+
+impl Serialize for KillError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("KillError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("pid", &self.pid())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<KillError> for String {
+    fn from(error: KillError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Why the listening ports could not be collected.
+#[derive(Debug)]
+pub(crate) enum ListPortsError {
+    /// The socket listing tool (`lsof` or `netstat`) is not installed or not on PATH.
+    ToolNotFound(String),
+    /// The tool exists but could not be spawned.
+    SpawnError(String),
+    /// The tool ran but exited unsuccessfully.
+    CommandFailed { tool: String, stderr: String },
+    /// The tool failed because it needs root (or `CAP_NET_ADMIN`) to see sockets.
+    /// Only reported on Linux: macOS `lsof` lists the current user's processes
+    /// without complaint and simply leaves the others out.
+    #[cfg(target_os = "linux")]
+    InsufficientPrivileges { tool: String, stderr: String },
+    /// The tool was still running after `timeout_ms` and was killed.
+    #[cfg(not(target_os = "windows"))]
+    Timeout { tool: String, timeout_ms: u64 },
+    /// As many listings as allowed were already being collected and the
+    /// collection limit is set not to queue.
+    Busy { request_id: u64 },
+}
+
+impl ListPortsError {
+    /// Classifies a failed run of `tool` from its stderr.
+    #[cfg(not(target_os = "windows"))]
+    pub(crate) fn from_failure(tool: &str, stderr: String) -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            let lower = stderr.to_lowercase();
+            if lower.contains("must be root") || lower.contains("permission denied") {
+                return ListPortsError::InsufficientPrivileges { tool: tool.to_string(), stderr };
+            }
+        }
+        ListPortsError::CommandFailed { tool: tool.to_string(), stderr }
+    }
+
+    /// Classifies a failure to run `tool` at all, including the `TimedOut`
+    /// that `tools::output_within` gives once `timeout_ms` has passed.
+    #[cfg(not(target_os = "windows"))]
+    pub(crate) fn from_spawn(tool: &str, timeout_ms: u64, error: &io::Error) -> Self {
+        tracing::warn!("Failed to execute {}: {}", tool, error);
+        match error.kind() {
+            io::ErrorKind::NotFound => ListPortsError::ToolNotFound(tool.to_string()),
+            io::ErrorKind::TimedOut => ListPortsError::Timeout {
+                tool: tool.to_string(),
+                timeout_ms,
+            },
+            _ => ListPortsError::SpawnError(super::tools::spawn_error(tool, error)),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            ListPortsError::ToolNotFound(_) => "toolNotFound",
+            ListPortsError::SpawnError(_) => "spawnError",
+            ListPortsError::CommandFailed { .. } => "commandFailed",
+            #[cfg(target_os = "linux")]
+            ListPortsError::InsufficientPrivileges { .. } => "insufficientPrivileges",
+            #[cfg(not(target_os = "windows"))]
+            ListPortsError::Timeout { .. } => "timeout",
+            ListPortsError::Busy { .. } => "busy",
+        }
+    }
+}
+
+impl fmt::Display for ListPortsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(not(target_os = "windows"))]
+            ListPortsError::ToolNotFound(tool) => match super::tools::install_hint(tool) {
+                Some(hint) => write!(f, "{} is not installed. Install it with: {}", tool, hint),
+                None => write!(f, "{} was not found on PATH", tool),
+            },
+            #[cfg(target_os = "windows")]
+            ListPortsError::ToolNotFound(tool) => write!(f, "{} was not found on PATH", tool),
+            ListPortsError::SpawnError(message) => f.write_str(message),
+            ListPortsError::CommandFailed { tool, stderr } if stderr.is_empty() => write!(f, "{} command failed", tool),
+            ListPortsError::CommandFailed { tool, stderr } => write!(f, "{} command failed: {}", tool, stderr),
+            #[cfg(target_os = "linux")]
+            ListPortsError::InsufficientPrivileges { tool, stderr } => {
+                write!(f, "{} needs elevated permissions: {}", tool, stderr)
+            }
+            #[cfg(not(target_os = "windows"))]
+            ListPortsError::Timeout { tool, timeout_ms } => {
+                write!(f, "{} did not finish within {} ms and was stopped", tool, timeout_ms)
+            }
+            ListPortsError::Busy { request_id } => {
+                write!(f, "Request {} refused: a listing is already being collected", request_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ListPortsError {}
+
+impl Serialize for ListPortsError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ListPortsError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<ListPortsError> for String {
+    fn from(error: ListPortsError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,234 @@
+//! Writes a listing to disk, either as pretty-printed JSON wrapped with enough
+//! context (when, which machine, which OS) to be useful in an incident report,
+//! or as a flat CSV for pasting into a spreadsheet. Also formats one as
+//! Prometheus exposition text for `get_metrics` and the API's `/metrics`.
+
+use super::audit::utc_timestamp;
+use super::PortInfo;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+#[derive(Debug, Serialize)]
+struct ExportMetadata {
+    /// UTC, RFC 3339 (e.g. `"2025-01-31T12:00:00Z"`).
+    exported_at: String,
+    hostname: String,
+    os_version: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Export<'a> {
+    metadata: ExportMetadata,
+    ports: &'a [PortInfo],
+}
+
+/// Writes `ports` to `path`, replacing any existing file.
+pub(crate) fn write_json(path: &Path, ports: &[PortInfo]) -> Result<(), String> {
+    let export = Export {
+        metadata: ExportMetadata {
+            exported_at: utc_timestamp(SystemTime::now()),
+            hostname: hostname(),
+            os_version: os_version(),
+        },
+        ports,
+    };
+
+    let file = File::create(path).map_err(|e| create_error(path, e))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, &export).map_err(|e| format!("Failed to serialize export: {}", e))?;
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Writes `ports` to `path` as CSV with one row per PID, repeating the group's
+/// `process_name` and `command` on each. `ports` is comma-joined in one field
+/// (quoted by the writer) and unavailable `cpu`/`mem` readings are left empty.
+pub(crate) fn write_csv(path: &Path, ports: &[PortInfo]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| create_error(path, e))?;
+    let mut writer = csv::Writer::from_writer(file);
+    let write_error = |e: csv::Error| format!("Failed to write {}: {}", path.display(), e);
+
+    writer
+        .write_record(["process_name", "command", "pid", "ports", "user", "cpu", "mem"])
+        .map_err(write_error)?;
+    for port_info in ports {
+        for pid_info in &port_info.pids {
+            let port_list = pid_info.ports.iter().map(u16::to_string).collect::<Vec<_>>().join(",");
+            let reading = |value: Option<f32>| value.map(|v| v.to_string()).unwrap_or_default();
+            writer
+                .write_record([
+                    port_info.process_name.as_str(),
+                    port_info.command.as_str(),
+                    &pid_info.pid.to_string(),
+                    &port_list,
+                    &pid_info.user,
+                    &reading(pid_info.cpu),
+                    &reading(pid_info.mem),
+                ])
+                .map_err(write_error)?;
+        }
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// One PID's figures in `prometheus` output.
+struct MetricSample<'a> {
+    process: &'a str,
+    pid: u32,
+    cpu: Option<f32>,
+    mem: Option<f32>,
+    ports: BTreeSet<u16>,
+}
+
+/// Formats `ports` in the Prometheus text exposition format: per PID, gauges
+/// for CPU and memory percentage and the number of ports it listens on,
+/// labelled with the process name and PID. A PID listed once per protocol or
+/// bind address is merged into one sample, since Prometheus rejects repeated
+/// label sets, and unavailable CPU or memory readings are left out.
+pub(crate) fn prometheus(ports: &[PortInfo]) -> String {
+    let mut samples: Vec<MetricSample> = Vec::new();
+    let mut index: HashMap<(&str, u32), usize> = HashMap::new();
+    for port_info in ports {
+        for pid_info in &port_info.pids {
+            let i = *index.entry((&port_info.process_name, pid_info.pid)).or_insert_with(|| {
+                samples.push(MetricSample {
+                    process: &port_info.process_name,
+                    pid: pid_info.pid,
+                    cpu: pid_info.cpu,
+                    mem: pid_info.mem,
+                    ports: BTreeSet::new(),
+                });
+                samples.len() - 1
+            });
+            samples[i].ports.extend(&pid_info.ports);
+        }
+    }
+
+    let mut text = String::new();
+    write_gauge(
+        &mut text,
+        "process_monitor_cpu_percent",
+        "CPU usage of a process with listening sockets, in percent of one core.",
+        samples.iter().filter_map(|sample| Some((sample, sample.cpu?))),
+    );
+    write_gauge(
+        &mut text,
+        "process_monitor_memory_percent",
+        "Resident memory of a process with listening sockets, in percent of physical memory.",
+        samples.iter().filter_map(|sample| Some((sample, sample.mem?))),
+    );
+    write_gauge(
+        &mut text,
+        "process_monitor_port_count",
+        "Number of distinct ports a process listens on.",
+        samples.iter().map(|sample| (sample, sample.ports.len() as f32)),
+    );
+    text
+}
+
+/// Appends one gauge family: its `# HELP` and `# TYPE` lines, then a sample per PID.
+fn write_gauge<'a>(
+    text: &mut String,
+    name: &str,
+    help: &str,
+    values: impl Iterator<Item = (&'a MetricSample<'a>, f32)>,
+) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} gauge", name);
+    for (sample, value) in values {
+        let process = escape_label(sample.process);
+        let _ = writeln!(text, "{}{{process=\"{}\",pid=\"{}\"}} {}", name, process, sample.pid, value);
+    }
+}
+
+/// Escapes a label value the way the exposition format requires: backslash,
+/// double quote and line feed.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn create_error(path: &Path, e: io::Error) -> String {
+    match e.kind() {
+        ErrorKind::NotFound => format!("Directory for {} does not exist", path.display()),
+        ErrorKind::PermissionDenied => format!("{} is not writable", path.display()),
+        _ => format!("Failed to create {}: {}", path.display(), e),
+    }
+}
+
+/// The `hostname` binary exists on every supported platform, unlike the
+/// `HOSTNAME` variable, which shells set but don't usually export.
+fn hostname() -> String {
+    command_output(Command::new("hostname"))
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_default()
+}
+
+/// Kernel name and release on Unix (`"Linux 6.8.0"`, `"Darwin 23.4.0"`), the
+/// `ver` banner on Windows. Falls back to the bare OS name.
+fn os_version() -> String {
+    #[cfg(not(target_os = "windows"))]
+    let command = {
+        let mut command = Command::new("uname");
+        command.arg("-sr");
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "ver"]);
+        command
+    };
+
+    command_output(command).unwrap_or_else(|| std::env::consts::OS.to_string())
+}
+
+/// Trimmed stdout of `command`, or None if it failed or printed nothing.
+fn command_output(mut command: Command) -> Option<String> {
+    let output = command.output().ok().filter(|o| o.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PidInfo, ProcessDetails};
+
+    #[test]
+    fn prometheus_merges_pids_and_escapes_labels() {
+        let pid_info = |protocol: &str, ports: Vec<u16>| {
+            let details = ProcessDetails {
+                cpu: Some(1.5),
+                ..ProcessDetails::default()
+            };
+            let (protocol, state, bind_address) = (protocol.to_string(), "LISTEN".to_string(), "*".to_string());
+            PidInfo::from_details(100, protocol, state, ports, bind_address, details)
+        };
+        let ports = [PortInfo {
+            process_name: "my \"app\"".to_string(),
+            command: "app --serve".to_string(),
+            pids: vec![pid_info("TCP", vec![80, 443]), pid_info("TCP6", vec![80])],
+            connection_count: 0,
+        }];
+
+        let text = prometheus(&ports);
+        let labels = r#"{process="my \"app\"",pid="100"}"#;
+        // One sample for the PID, though it is listed once per protocol
+        assert_eq!(text.matches("process_monitor_cpu_percent{").count(), 1);
+        assert!(text.contains(&format!("process_monitor_cpu_percent{} 1.5\n", labels)));
+        // No memory reading, so the family has no samples
+        assert!(!text.contains("process_monitor_memory_percent{"));
+        // Port 80 over both TCP and TCP6 counts once
+        assert!(text.contains(&format!("process_monitor_port_count{} 2\n", labels)));
+        assert_eq!(text.matches("# TYPE ").count(), 3);
+    }
+}
@@ -0,0 +1,164 @@
+//! FreeBSD socket listing: `sockstat` ships in base while `lsof` is an optional
+//! package, so listening and connected sockets are read from it instead.
+//! Process details still come from `ps`, which the BSD one answers the same way.
+
+use super::{split_address, tools, ListPortsError, SocketEntry};
+use std::collections::HashMap;
+use std::process::Command;
+
+/// Runs `sockstat` with the given arguments and returns its stdout. It stands in
+/// for `lsof`, so it gets the same timeout.
+fn run_sockstat(args: &[&str]) -> Result<String, ListPortsError> {
+    let timeout_ms = tools::lsof_timeout_ms();
+    let output = tools::output_within(Command::new("sockstat").args(args), timeout_ms)
+        .map_err(|e| ListPortsError::from_spawn("sockstat", timeout_ms, &e))?;
+
+    if !output.status.success() {
+        tracing::warn!("sockstat command failed with status: {}", output.status);
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(ListPortsError::from_failure("sockstat", stderr));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Listening TCP sockets and every bound UDP socket, as `lsof -sTCP:LISTEN` and
+/// `lsof -iUDP` would give them, optionally limited to those `user` owns.
+pub(crate) fn listening_sockets(user: Option<&str>) -> Result<Vec<SocketEntry>, ListPortsError> {
+    // -l alone would drop UDP sockets, which are never in a listening state
+    let tcp_stdout = run_sockstat(&["-4", "-6", "-l", "-P", "tcp"])?;
+    let udp_stdout = run_sockstat(&["-4", "-6", "-P", "udp"])?;
+
+    let mut sockets = parse_sockstat(&tcp_stdout, user);
+    for socket in &mut sockets {
+        socket.state = "LISTEN".to_string();
+    }
+    sockets.extend(parse_sockstat(&udp_stdout, user));
+    Ok(sockets)
+}
+
+/// Every TCP and UDP socket, listening or connected, as `lsof -i` would give
+/// them. Connected TCP sockets come from `sockstat -c -s`, whose last column is
+/// the TCP state; bound UDP sockets are all among the listeners already.
+pub(crate) fn all_sockets() -> Result<Vec<SocketEntry>, ListPortsError> {
+    let mut sockets = listening_sockets(None)?;
+    sockets.extend(parse_sockstat(&run_sockstat(&["-4", "-6", "-c", "-s", "-P", "tcp"])?, None));
+    Ok(sockets)
+}
+
+/// Connected TCP and UDP sockets per PID, from `sockstat -c`.
+pub(crate) fn connections_per_pid() -> Result<HashMap<u32, u32>, ListPortsError> {
+    let stdout = run_sockstat(&["-4", "-6", "-c"])?;
+
+    let mut per_pid: HashMap<u32, u32> = HashMap::new();
+    for socket in parse_sockstat(&stdout, None) {
+        *per_pid.entry(socket.pid).or_default() += 1;
+    }
+    Ok(per_pid)
+}
+
+/// Parses `sockstat` rows such as
+///
+/// ```text
+/// USER     COMMAND    PID   FD  PROTO  LOCAL ADDRESS         FOREIGN ADDRESS
+/// www      nginx      1201  6   tcp4   *:80                  *:*
+/// root     ntpd       911   21  udp6   fe80::1%lo0:123       *:*
+/// ```
+///
+/// keeping the TCP and UDP ones `user` owns. With `-s` a TCP state such as
+/// `ESTABLISHED` follows the foreign address. IPv6 addresses aren't bracketed,
+/// which `split_address` handles by splitting at the last colon. Sockets whose
+/// process has exited show `?` for the user, command and PID and are skipped
+/// along with the header.
+fn parse_sockstat(stdout: &str, user: Option<&str>) -> Vec<SocketEntry> {
+    tracing::debug!("Parsing sockstat output, {} lines", stdout.lines().count());
+
+    let mut sockets = Vec::new();
+
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 7 || user.is_some_and(|user| user != parts[0]) {
+            continue;
+        }
+        let Ok(pid) = parts[2].parse::<u32>() else {
+            continue;
+        };
+        // tcp46 is a dual-stack socket bound with IPV6_V6ONLY off
+        let protocol = match parts[4] {
+            "tcp4" => "TCP",
+            "tcp6" | "tcp46" => "TCP6",
+            "udp4" => "UDP",
+            "udp6" | "udp46" => "UDP6",
+            _ => continue,
+        };
+
+        if let Some((bind_address, port)) = split_address(parts[5]) {
+            sockets.push(SocketEntry {
+                process_name: parts[1].to_string(),
+                pid,
+                protocol: protocol.to_string(),
+                port,
+                bind_address,
+                // sockstat has no state column without -s
+                state: parts.get(7).map(|state| state.to_string()).unwrap_or_default(),
+            });
+        }
+    }
+
+    sockets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOCKSTAT: &str = "\
+USER     COMMAND    PID   FD  PROTO  LOCAL ADDRESS         FOREIGN ADDRESS
+www      nginx      1201  6   tcp4   *:80                  *:*
+root     sshd       845   4   tcp6   *:22                  *:*
+root     node       1302  18  tcp46  *:3000                *:*
+root     ntpd       911   21  udp6   fe80::1%lo0:123       *:*
+root     syslogd    650   6   udp4   127.0.0.1:514         *:*
+?        ?          ?     ?   tcp4   *:8080                *:*
+root     devd       431   4   stream /var/run/devd.pipe
+";
+
+    fn summary(sockets: &[SocketEntry]) -> Vec<(&str, u32, &str, &str, u16)> {
+        sockets
+            .iter()
+            .map(|s| (s.process_name.as_str(), s.pid, s.protocol.as_str(), s.bind_address.as_str(), s.port))
+            .collect()
+    }
+
+    #[test]
+    fn parse_sockstat_reads_inet_sockets() {
+        let sockets = parse_sockstat(SOCKSTAT, None);
+        assert_eq!(
+            summary(&sockets),
+            [
+                ("nginx", 1201, "TCP", "*", 80),
+                ("sshd", 845, "TCP6", "*", 22),
+                ("node", 1302, "TCP6", "*", 3000),
+                ("ntpd", 911, "UDP6", "fe80::1%lo0", 123),
+                ("syslogd", 650, "UDP", "127.0.0.1", 514),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_sockstat_reads_the_tcp_state() {
+        let stdout = "\
+USER     COMMAND    PID   FD  PROTO  LOCAL ADDRESS         FOREIGN ADDRESS       STATE
+www      nginx      1201  7   tcp4   10.0.0.5:80           10.0.0.9:51234        ESTABLISHED
+alice    ssh        2210  3   tcp6   2001:db8::5:50122     2001:db8::1:22        CLOSE_WAIT
+";
+        let states: Vec<_> = parse_sockstat(stdout, None).into_iter().map(|s| (s.pid, s.state)).collect();
+        assert_eq!(states, [(1201, "ESTABLISHED".to_string()), (2210, "CLOSE_WAIT".to_string())]);
+    }
+
+    #[test]
+    fn parse_sockstat_keeps_only_the_given_user() {
+        let sockets = parse_sockstat(SOCKSTAT, Some("www"));
+        assert_eq!(summary(&sockets), [("nginx", 1201, "TCP", "*", 80)]);
+    }
+}
@@ -0,0 +1,1010 @@
+//! Per-process lookups that `ps` doesn't cover. Linux reads `/proc/<pid>`
+//! directly; macOS asks `lsof` about specific file descriptors.
+
+use super::{DiskIO, MemoryRegion, ResourceLimits, SchedulerInfo, SharedLib};
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::io;
+#[cfg(not(target_os = "windows"))]
+use super::tools;
+#[cfg(target_os = "macos")]
+use super::ListPortsError;
+
+/// Runs `lsof` within its timeout and returns its stdout whatever the exit
+/// status, since it exits 1 when some descriptors were unreadable yet still
+/// lists the rest.
+#[cfg(target_os = "macos")]
+fn lsof_stdout(args: &[&str]) -> Result<String, String> {
+    let timeout_ms = tools::lsof_timeout_ms();
+    let output = tools::output_within(tools::lsof().args(args), timeout_ms)
+        .map_err(|e| ListPortsError::from_spawn("lsof", timeout_ms, &e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the `n` (name) fields `lsof -Fn` reports for descriptor `fd` of `pid`.
+#[cfg(target_os = "macos")]
+fn lsof_fd_names(pid: u32, fd: &str) -> Result<Vec<String>, String> {
+    Ok(lsof_stdout(&["-a", "-p", &pid.to_string(), "-d", fd, "-Fn"])?
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Resolves the current working directory of `pid`.
+#[cfg(target_os = "linux")]
+pub(crate) fn cwd(pid: u32) -> Result<String, String> {
+    fs::read_link(format!("/proc/{}/cwd", pid))
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| format!("Failed to read working directory of process {}: {}", pid, e))
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn cwd(pid: u32) -> Result<String, String> {
+    lsof_fd_names(pid, "cwd")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Failed to read working directory of process {}", pid))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn cwd(_pid: u32) -> Result<String, String> {
+    Err("Reading the working directory is not supported on this platform".to_string())
+}
+
+/// Counts the open file descriptors of `pid`.
+#[cfg(target_os = "linux")]
+pub(crate) fn fd_count(pid: u32) -> Result<u32, String> {
+    fs::read_dir(format!("/proc/{}/fd", pid))
+        .map(|entries| entries.count() as u32)
+        .map_err(|e| format!("Failed to read descriptors of process {}: {}", pid, e))
+}
+
+/// Approximates the descriptor count on macOS with one `lsof -p` row per open file.
+#[cfg(target_os = "macos")]
+pub(crate) fn fd_count(pid: u32) -> Result<u32, String> {
+    // Skip the header row
+    Ok(lsof_stdout(&["-p", &pid.to_string()])?.lines().skip(1).count() as u32)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(crate) fn fd_count(_pid: u32) -> Result<u32, String> {
+    Err("Counting file descriptors is not supported on this platform".to_string())
+}
+
+/// Resolves the executable `pid` is running, independent of how it was invoked.
+#[cfg(target_os = "linux")]
+pub(crate) fn exe_path(pid: u32) -> Result<String, String> {
+    fs::read_link(format!("/proc/{}/exe", pid))
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| format!("Failed to read executable of process {}: {}", pid, e))
+}
+
+/// On macOS the executable is the first `txt` descriptor; later ones are loaded libraries.
+#[cfg(target_os = "macos")]
+pub(crate) fn exe_path(pid: u32) -> Result<String, String> {
+    lsof_fd_names(pid, "txt")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Failed to read executable of process {}", pid))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(crate) fn exe_path(_pid: u32) -> Result<String, String> {
+    Err("Reading the executable path is not supported on this platform".to_string())
+}
+
+/// Reads the limits from `/proc/<pid>/limits`, whose rows look like
+/// `Max open files            1024                 524288               files`
+/// (soft, then hard).
+#[cfg(target_os = "linux")]
+pub(crate) fn resource_limits(pid: u32) -> Result<ResourceLimits, String> {
+    let contents = fs::read_to_string(format!("/proc/{}/limits", pid))
+        .map_err(|e| format!("Failed to read limits of process {}: {}", pid, e))?;
+
+    // "unlimited" doesn't parse, which is exactly the None we want
+    let limit = |label: &str| -> (Option<u64>, Option<u64>) {
+        let mut values = contents
+            .lines()
+            .find_map(|line| line.strip_prefix(label))
+            .map(|rest| rest.split_whitespace())
+            .into_iter()
+            .flatten()
+            .map(|value| value.parse().ok());
+        (values.next().flatten(), values.next().flatten())
+    };
+
+    let (max_open_files, max_open_files_hard) = limit("Max open files");
+    let (max_processes, max_processes_hard) = limit("Max processes");
+    Ok(ResourceLimits {
+        max_open_files,
+        max_open_files_hard,
+        max_memory_size: limit("Max resident set").0,
+        max_virtual_memory: limit("Max address space").0,
+        max_stack_size: limit("Max stack size").0,
+        max_cpu_time: limit("Max cpu time").0,
+        max_processes,
+        max_processes_hard,
+        open_files: fd_count(pid).ok(),
+        ..ResourceLimits::default()
+    })
+}
+
+/// macOS has no way to read another process's limits without a debugger, so
+/// these are the defaults launchd gives new processes, from `launchctl limit`:
+///
+/// ```text
+///     stack       8388608        67104768
+///     maxproc     2666           4000
+///     maxfiles    256            unlimited
+/// ```
+///
+/// launchd has no address space limit, so `max_virtual_memory` is always None.
+#[cfg(target_os = "macos")]
+pub(crate) fn resource_limits(pid: u32) -> Result<ResourceLimits, String> {
+    let output = std::process::Command::new("launchctl")
+        .arg("limit")
+        .output()
+        .map_err(|e| format!("Failed to execute launchctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "launchctl limit failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let limit = |name: &str| -> (Option<u64>, Option<u64>) {
+        let row = stdout.lines().find_map(|line| {
+            let mut columns = line.split_whitespace();
+            (columns.next() == Some(name)).then(|| {
+                let mut values = columns.map(|value| value.parse().ok());
+                (values.next().flatten(), values.next().flatten())
+            })
+        });
+        row.unwrap_or_default()
+    };
+
+    let (max_open_files, max_open_files_hard) = limit("maxfiles");
+    let (max_processes, max_processes_hard) = limit("maxproc");
+    Ok(ResourceLimits {
+        max_open_files,
+        max_open_files_hard,
+        max_memory_size: limit("rss").0,
+        max_stack_size: limit("stack").0,
+        max_cpu_time: limit("cpu").0,
+        max_processes,
+        max_processes_hard,
+        open_files: fd_count(pid).ok(),
+        ..ResourceLimits::default()
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn resource_limits(_pid: u32) -> Result<ResourceLimits, String> {
+    Err("Reading resource limits is not supported on this platform".to_string())
+}
+
+/// Reads the environment of `pid` from the NUL-separated `KEY=VALUE` pairs in
+/// `/proc/<pid>/environ`, which only the owner (or root) may read.
+#[cfg(target_os = "linux")]
+pub(crate) fn environ(pid: u32) -> Result<HashMap<String, String>, String> {
+    let contents = fs::read(format!("/proc/{}/environ", pid)).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => format!("Permission denied reading environment of process {}", pid),
+        _ => format!("Failed to read environment of process {}: {}", pid, e),
+    })?;
+
+    Ok(contents
+        .split(|&b| b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (name, value) = entry.split_once('=')?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect())
+}
+
+/// `ps -E` appends the environment to the command line, so the plain command
+/// line is stripped off first and the rest split into `KEY=VALUE` words. Values
+/// containing spaces are truncated, and processes of other users report nothing.
+#[cfg(target_os = "macos")]
+pub(crate) fn environ(pid: u32) -> Result<HashMap<String, String>, String> {
+    let command_line = |with_env: bool| -> Result<String, String> {
+        let mut args = vec!["-ww", "-p"];
+        if with_env {
+            args.insert(0, "-E");
+        }
+        let output = tools::ps()
+            .args(args)
+            .args([&pid.to_string(), "-o", "command="])
+            .output()
+            .map_err(|e| tools::spawn_error("ps", &e))?;
+        if !output.status.success() {
+            return Err(format!("Failed to read environment of process {}", pid));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let command = command_line(false)?;
+    let with_env = command_line(true)?;
+    let env = with_env.strip_prefix(command.as_str()).unwrap_or_default();
+
+    Ok(env
+        .split_whitespace()
+        .filter_map(|word| word.split_once('='))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn environ(_pid: u32) -> Result<HashMap<String, String>, String> {
+    Err("Reading the environment is not supported on this platform".to_string())
+}
+
+/// Reads the untruncated argv of `pid` from the NUL-separated `/proc/<pid>/cmdline`.
+/// Kernel threads and zombies have an empty command line.
+#[cfg(target_os = "linux")]
+pub(crate) fn args(pid: u32) -> Result<Vec<String>, String> {
+    let contents = fs::read(format!("/proc/{}/cmdline", pid))
+        .map_err(|e| format!("Failed to read arguments of process {}: {}", pid, e))?;
+
+    if contents.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Every argument is NUL-terminated; empty arguments in between are kept
+    Ok(contents
+        .strip_suffix(&[0])
+        .unwrap_or(&contents)
+        .split(|&b| b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect())
+}
+
+/// `-ww` lifts the column limit, but `ps` still joins argv with spaces, so
+/// arguments that themselves contain spaces come back split.
+#[cfg(target_os = "macos")]
+pub(crate) fn args(pid: u32) -> Result<Vec<String>, String> {
+    let output = tools::ps()
+        .args(["-ww", "-p", &pid.to_string(), "-o", "command="])
+        .output()
+        .map_err(|e| tools::spawn_error("ps", &e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to read arguments of process {}", pid));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn args(_pid: u32) -> Result<Vec<String>, String> {
+    Err("Reading process arguments is not supported on this platform".to_string())
+}
+
+/// Returns the short name of the executable `pid` is running (at most 15 bytes on Linux).
+#[cfg(target_os = "linux")]
+pub(crate) fn name(pid: u32) -> Result<String, String> {
+    fs::read_to_string(format!("/proc/{}/comm", pid))
+        .map(|comm| comm.trim_end().to_string())
+        .map_err(|e| format!("Failed to read name of process {}: {}", pid, e))
+}
+
+/// `comm` on macOS is the full executable path, so only its last component is kept.
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+pub(crate) fn name(pid: u32) -> Result<String, String> {
+    let output = tools::ps()
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .map_err(|e| tools::spawn_error("ps", &e))?;
+    let comm = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match comm.rsplit('/').next() {
+        Some(name) if !name.is_empty() => Ok(name.to_string()),
+        _ => Err(format!("Failed to read name of process {}", pid)),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn name(pid: u32) -> Result<String, String> {
+    super::windows::image_name(pid).ok_or_else(|| format!("Failed to read name of process {}", pid))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd", target_os = "windows")))]
+pub(crate) fn name(_pid: u32) -> Result<String, String> {
+    Err("Reading the process name is not supported on this platform".to_string())
+}
+
+/// Returns the name of the user `pid` runs as, the way the listing's `user` shows it.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn user(pid: u32) -> Result<String, String> {
+    let output = tools::ps()
+        .args(["-p", &pid.to_string(), "-o", "user="])
+        .output()
+        .map_err(|e| tools::spawn_error("ps", &e))?;
+    let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if user.is_empty() {
+        return Err(format!("Failed to read user of process {}", pid));
+    }
+    Ok(user)
+}
+
+/// `DOMAIN\name`, as `tasklist /V` prints it.
+#[cfg(target_os = "windows")]
+pub(crate) fn user(pid: u32) -> Result<String, String> {
+    super::windows::process_user(pid).ok_or_else(|| format!("Failed to read user of process {}", pid))
+}
+
+/// Reads the OOM killer's current badness score for `pid`.
+#[cfg(target_os = "linux")]
+pub(crate) fn oom_score(pid: u32) -> Result<i32, String> {
+    read_proc_number(pid, "oom_score")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn oom_score(_pid: u32) -> Result<i32, String> {
+    Err("OOM scores are only available on Linux".to_string())
+}
+
+/// Reads the adjustment added to `pid`'s OOM score.
+#[cfg(target_os = "linux")]
+pub(crate) fn oom_score_adj(pid: u32) -> Result<i32, String> {
+    read_proc_number(pid, "oom_score_adj")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn oom_score_adj(_pid: u32) -> Result<i32, String> {
+    Err("OOM scores are only available on Linux".to_string())
+}
+
+/// Writes `/proc/<pid>/oom_score_adj`; the kernel rejects decreases from non-root users.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_oom_score_adj(pid: u32, adj: i32) -> Result<(), String> {
+    fs::write(format!("/proc/{}/oom_score_adj", pid), adj.to_string()).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => {
+            format!("Permission denied: lowering the OOM score of process {} requires root", pid)
+        }
+        _ => format!("Failed to set OOM score adjustment of process {}: {}", pid, e),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_oom_score_adj(_pid: u32, _adj: i32) -> Result<(), String> {
+    Err("OOM scores are only available on Linux".to_string())
+}
+
+/// Reads the CPUs `pid` may run on from the `Cpus_allowed_list` line of
+/// `/proc/<pid>/status`, which is in the same `"0-3,7"` form `taskset -cp` prints.
+#[cfg(target_os = "linux")]
+pub(crate) fn cpu_affinity(pid: u32) -> Result<Vec<u32>, String> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid))
+        .map_err(|e| format!("Failed to read CPU affinity of process {}: {}", pid, e))?;
+    let list = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+        .ok_or_else(|| format!("Status of process {} has no CPU affinity", pid))?;
+    parse_cpu_list(list.trim()).ok_or_else(|| format!("Failed to parse CPU affinity {:?}", list.trim()))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn cpu_affinity(_pid: u32) -> Result<Vec<u32>, String> {
+    Err("CPU affinity is only available on Linux".to_string())
+}
+
+/// Restricts `pid` to `cpus` with `taskset -cp`, since `sched_setaffinity` has
+/// no file interface. Changing another user's process requires root.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_cpu_affinity(pid: u32, cpus: &[u32]) -> Result<(), String> {
+    let list = cpus.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+    let output = std::process::Command::new("taskset")
+        .args(["-cp", &list, &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to execute taskset: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to set CPU affinity of process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_cpu_affinity(_pid: u32, _cpus: &[u32]) -> Result<(), String> {
+    Err("CPU affinity is only available on Linux".to_string())
+}
+
+/// Reads the scheduling policy and real-time priority of `pid` from
+/// `/proc/<pid>/stat`, the same values `chrt -p` prints.
+#[cfg(target_os = "linux")]
+pub(crate) fn scheduler_info(pid: u32) -> Result<SchedulerInfo, String> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map_err(|e| format!("Failed to read scheduler of process {}: {}", pid, e))?;
+    parse_scheduler(&stat).map_err(|e| format!("Process {}: {}", pid, e))
+}
+
+/// Takes fields 41 (`policy`) and 40 (`rt_priority`) of a `/proc/<pid>/stat`
+/// line. Fields are counted from the last `)`, since the command name before
+/// it may contain spaces.
+#[cfg(target_os = "linux")]
+fn parse_scheduler(stat: &str) -> Result<SchedulerInfo, String> {
+    // fields[0] is field 3 (state) in proc(5) numbering
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map(|(_, rest)| rest.split_whitespace().collect())
+        .unwrap_or_default();
+    let field = |index: usize| -> Result<u32, String> {
+        fields
+            .get(index)
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(|| "stat has no scheduler fields".to_string())
+    };
+
+    // Numbering from <linux/sched.h>; 4 is unused
+    let policy = match field(38)? {
+        0 => "SCHED_OTHER",
+        1 => "SCHED_FIFO",
+        2 => "SCHED_RR",
+        3 => "SCHED_BATCH",
+        5 => "SCHED_IDLE",
+        6 => "SCHED_DEADLINE",
+        other => return Err(format!("unknown scheduling policy {}", other)),
+    };
+    Ok(SchedulerInfo {
+        policy: policy.to_string(),
+        priority: field(37)? as i32,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn scheduler_info(_pid: u32) -> Result<SchedulerInfo, String> {
+    Err("Scheduler policies are only available on Linux".to_string())
+}
+
+/// Expands a kernel CPU list such as `"0-3,7"` into `[0, 1, 2, 3, 7]`.
+#[cfg(target_os = "linux")]
+fn parse_cpu_list(list: &str) -> Option<Vec<u32>> {
+    let mut cpus = Vec::new();
+    for range in list.split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<u32>().ok()?..=last.parse::<u32>().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+#[cfg(target_os = "linux")]
+fn read_proc_number(pid: u32, file: &str) -> Result<i32, String> {
+    fs::read_to_string(format!("/proc/{}/{}", pid, file))
+        .map_err(|e| format!("Failed to read {} of process {}: {}", file, pid, e))?
+        .trim()
+        .parse()
+        .map_err(|e| format!("Failed to parse {} of process {}: {}", file, pid, e))
+}
+
+/// Reads the storage counters of `/proc/<pid>/io` (`read_bytes: 4096` lines).
+/// The file needs the same access as ptrace, so other users' processes fail
+/// with a permission error unless we run as root.
+#[cfg(target_os = "linux")]
+pub(crate) fn disk_io(pid: u32) -> Result<DiskIO, String> {
+    let contents = fs::read_to_string(format!("/proc/{}/io", pid)).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => format!("Not permitted to read I/O counters of process {}", pid),
+        io::ErrorKind::NotFound => format!("Process {} not found", pid),
+        _ => format!("Failed to read I/O counters of process {}: {}", pid, e),
+    })?;
+
+    let counter = |key: &str| -> Result<u64, String> {
+        contents
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|value| value.trim().parse().ok())
+            .ok_or_else(|| format!("I/O counters of process {} have no {} entry", pid, key))
+    };
+    Ok(DiskIO {
+        read_bytes: counter("read_bytes")?,
+        write_bytes: counter("write_bytes")?,
+        cancelled_write_bytes: counter("cancelled_write_bytes")?,
+    })
+}
+
+/// macOS keeps these in `proc_pid_rusage`, which needs a native call.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn disk_io(_pid: u32) -> Result<DiskIO, String> {
+    Err("Disk I/O counters are only available on Linux".to_string())
+}
+
+/// Lists the mappings of `pid` from `pmap -x`, whose rows after the command
+/// line and column header are `<address> <kbytes> <rss> <dirty> <mode> <mapping>`,
+/// ending in a `total kB` line. Anonymous mappings are named `[ anon ]`.
+#[cfg(target_os = "linux")]
+pub(crate) fn memory_map(pid: u32) -> Result<Vec<MemoryRegion>, String> {
+    let output = std::process::Command::new("pmap")
+        .args(["-x", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to execute pmap: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to read memory map of process {}", pid));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_pmap_row).collect())
+}
+
+/// One mapping row of `pmap -x`; None for the other lines.
+#[cfg(target_os = "linux")]
+fn parse_pmap_row(line: &str) -> Option<MemoryRegion> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    // Only mapping rows start with a hex address; the first line starts with "<pid>:"
+    let address = fields.first().filter(|a| a.len() >= 8 && a.chars().all(|c| c.is_ascii_hexdigit()))?;
+    if fields.len() < 6 {
+        return None;
+    }
+    Some(MemoryRegion {
+        address: address.to_string(),
+        size_kb: fields[1].parse().ok()?,
+        // Older kernels print "-" where they can't account a mapping
+        rss_kb: fields[2].parse().unwrap_or(0),
+        dirty_kb: fields[3].parse().ok(),
+        mapping: fields[5..].join(" "),
+    })
+}
+
+/// Summarizes the address space of `pid` from `vmmap -summary`, one region per
+/// region type ("MALLOC_SMALL", "__TEXT", ...) since the summary has no
+/// addresses. Rows sit between the first two `===` rules, as
+/// `<type> <virtual> <resident> <dirty> ...` with sizes like `"8192K"` or `"2.3G"`.
+#[cfg(target_os = "macos")]
+pub(crate) fn memory_map(pid: u32) -> Result<Vec<MemoryRegion>, String> {
+    let output = std::process::Command::new("vmmap")
+        .args(["-summary", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to execute vmmap: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to read memory map of process {}", pid));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip_while(|line| !line.starts_with("==="))
+        .skip(1)
+        .take_while(|line| !line.starts_with("==="))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // Region types may contain spaces ("mapped file"), so the name ends at the first size
+            let first_size = fields.iter().position(|field| parse_vmmap_size(field).is_some())?;
+            let size = |offset: usize| fields.get(first_size + offset).and_then(|field| parse_vmmap_size(field));
+            Some(MemoryRegion {
+                address: String::new(),
+                size_kb: size(0)?,
+                rss_kb: size(1).unwrap_or(0),
+                dirty_kb: size(2),
+                mapping: fields[..first_size].join(" "),
+            })
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn memory_map(_pid: u32) -> Result<Vec<MemoryRegion>, String> {
+    Err("Reading the memory map is not supported on this platform".to_string())
+}
+
+/// Lists the shared objects mapped by `pid` from `/proc/<pid>/maps`, whose rows
+/// are `<start>-<end> <perms> <offset> <dev> <inode> <path>`. A library is mapped
+/// several times (one per segment), so rows are merged per path.
+#[cfg(target_os = "linux")]
+pub(crate) fn shared_libraries(pid: u32) -> Result<Vec<SharedLib>, String> {
+    let maps = fs::read_to_string(format!("/proc/{}/maps", pid))
+        .map_err(|e| format!("Failed to read memory maps of process {}: {}", pid, e))?;
+    Ok(merge_mappings(maps.lines().filter_map(maps_library)))
+}
+
+/// The path and address range of a `maps` row that maps a shared object.
+#[cfg(target_os = "linux")]
+fn maps_library(line: &str) -> Option<(&str, (u64, u64))> {
+    // The path is the sixth field and may itself contain spaces
+    let mut fields = line.splitn(6, char::is_whitespace);
+    let range = fields.next()?;
+    let path = fields.nth(4)?.trim();
+    // Keeps "libc.so.6" and "libfoo.so", along with a " (deleted)" suffix when the
+    // file was replaced on disk (e.g. by an upgrade); skips "[heap]", "[stack]" and anonymous rows
+    let file_name = path.rsplit('/').next()?;
+    if !path.starts_with('/') || !(file_name.ends_with(".so") || file_name.contains(".so.")) {
+        return None;
+    }
+    Some((path, parse_address_range(range)?))
+}
+
+/// Lists the libraries and frameworks mapped by `pid` from `vmmap`, whose rows
+/// look like `__TEXT  19a3c5000-19a3ce000  [ 36K ...] r-x/r-x SM=COW  /usr/lib/libfoo.dylib`.
+/// Framework binaries have no extension, so `.framework/` paths count too.
+#[cfg(target_os = "macos")]
+pub(crate) fn shared_libraries(pid: u32) -> Result<Vec<SharedLib>, String> {
+    let output = std::process::Command::new("vmmap")
+        .args(["-w", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to execute vmmap: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to read memory map of process {}", pid));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mappings = stdout.lines().filter_map(|line| {
+        let range = line.split_whitespace().find_map(parse_address_range)?;
+        // The path follows the SM= share mode column and may contain spaces
+        let (_, after_share_mode) = line.split_once(" SM=")?;
+        let path = after_share_mode.split_once(char::is_whitespace)?.1.trim();
+        if !path.starts_with('/') || !(path.ends_with(".dylib") || path.contains(".framework/")) {
+            return None;
+        }
+        Some((path, range))
+    });
+    Ok(merge_mappings(mappings))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn shared_libraries(_pid: u32) -> Result<Vec<SharedLib>, String> {
+    Err("Listing shared libraries is not supported on this platform".to_string())
+}
+
+/// Parses a `<start>-<end>` hex address range, as both `maps` and `vmmap` print it.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn parse_address_range(range: &str) -> Option<(u64, u64)> {
+    let (start, end) = range.split_once('-')?;
+    let start = u64::from_str_radix(start, 16).ok()?;
+    let end = u64::from_str_radix(end, 16).ok()?;
+    (start <= end).then_some((start, end))
+}
+
+/// Merges the per-segment mappings of each library into one entry at its lowest
+/// address, sized by the segments' total, in load order.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn merge_mappings<'a>(mappings: impl Iterator<Item = (&'a str, (u64, u64))>) -> Vec<SharedLib> {
+    let mut libraries: Vec<(String, u64, u64)> = Vec::new();
+    let mut index_by_path: HashMap<String, usize> = HashMap::new();
+    for (path, (start, end)) in mappings {
+        match index_by_path.get(path) {
+            Some(&index) => {
+                let (_, base, size) = &mut libraries[index];
+                *base = (*base).min(start);
+                *size += end - start;
+            }
+            None => {
+                index_by_path.insert(path.to_string(), libraries.len());
+                libraries.push((path.to_string(), start, end - start));
+            }
+        }
+    }
+
+    libraries
+        .into_iter()
+        .map(|(path, base, size)| SharedLib {
+            path,
+            base_address: format!("{:#x}", base),
+            size_kb: size / 1024,
+        })
+        .collect()
+}
+
+/// Parses a `vmmap` size (`"0K"`, `"892K"`, `"12.5M"`, `"2.3G"`) into KiB.
+#[cfg(target_os = "macos")]
+fn parse_vmmap_size(size: &str) -> Option<u64> {
+    let split = size.len().checked_sub(1)?;
+    let (number, unit) = size.split_at(split);
+    let kib_per_unit = match unit {
+        "K" => 1.0,
+        "M" => 1024.0,
+        "G" => 1024.0 * 1024.0,
+        "T" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * kib_per_unit) as u64)
+}
+
+/// Finds the Docker container `pid` runs in from `/proc/<pid>/cgroup`, whose
+/// paths end in e.g. `/docker/<id>` (cgroup v1), `/docker-<id>.scope` (systemd)
+/// or `/containers/<id>`. None outside containers or if the file can't be read.
+#[cfg(target_os = "linux")]
+pub(crate) fn container_id(pid: u32) -> Option<String> {
+    let contents = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    contents.lines().find_map(cgroup_container_id)
+}
+
+/// The container ID in one `hierarchy-id:controllers:path` line of `/proc/<pid>/cgroup`.
+#[cfg(target_os = "linux")]
+fn cgroup_container_id(line: &str) -> Option<String> {
+    let path = line.splitn(3, ':').nth(2)?;
+    let segments: Vec<&str> = path.split('/').collect();
+    segments.iter().enumerate().find_map(|(i, segment)| {
+        let scoped = segment.strip_prefix("docker-").and_then(|s| s.strip_suffix(".scope"));
+        let in_docker_dir = i > 0 && matches!(segments[i - 1], "docker" | "containers");
+        let id = scoped.or(in_docker_dir.then_some(*segment))?;
+        (id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())).then(|| id.to_string())
+    })
+}
+
+/// Docker Desktop on macOS runs containers in a VM, so no host PID belongs to one.
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn container_id(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Reads the cgroup v2 path of `pid` from its `0::<path>` line in
+/// `/proc/<pid>/cgroup`, e.g. `/system.slice/nginx.service`. None on systems
+/// that only mount cgroup v1.
+#[cfg(target_os = "linux")]
+pub(crate) fn cgroup_path(pid: u32) -> Option<String> {
+    let contents = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    // Pure v2 has only this line; hybrid setups list it after the v1 hierarchies
+    contents.lines().find_map(|line| line.strip_prefix("0::")).map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn cgroup_path(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Finds the systemd service `pid` belongs to from its cgroup path, e.g.
+/// `0::/system.slice/nginx.service`. User services nest under the user manager
+/// (`.../user@1000.service/app.slice/foo.service`), so the innermost one wins.
+/// None when the system wasn't booted with systemd.
+#[cfg(target_os = "linux")]
+pub(crate) fn systemd_unit(pid: u32) -> Option<String> {
+    // The check sd_booted(3) makes
+    if !std::path::Path::new("/run/systemd/system").is_dir() {
+        return None;
+    }
+
+    let contents = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    contents.lines().find_map(|line| {
+        // The unified hierarchy ("0::") or systemd's own named v1 hierarchy
+        let path = line
+            .strip_prefix("0::")
+            .or_else(|| line.split_once(":name=systemd:").map(|(_, path)| path))?;
+        path.rsplit('/').find(|segment| segment.ends_with(".service")).map(str::to_string)
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn systemd_unit(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Reads the launchd label of the job `pid` belongs to from `launchctl procinfo`,
+/// which names it in a `<domain>/<label> = {` block header such as
+/// `system/com.apple.mDNSResponder = {`. `procinfo` needs root, so this is
+/// None for an unprivileged app as well as for processes launchd didn't start.
+#[cfg(target_os = "macos")]
+pub(crate) fn launchd_label(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("launchctl")
+        .args(["procinfo", &pid.to_string()])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let target = line.trim().strip_suffix(" = {")?;
+        let (domain, label) = target.rsplit_once('/')?;
+        let known_domain = ["system", "gui/", "user/", "login/", "pid/"]
+            .iter()
+            .any(|prefix| domain.starts_with(prefix));
+        (known_domain && !label.is_empty()).then(|| label.to_string())
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn launchd_label(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Reads the architecture from the ELF header of `/proc/<pid>/exe`, which still
+/// works after the file on disk has been replaced. Read directly rather than via
+/// `file`, which would be another subprocess per PID.
+#[cfg(target_os = "linux")]
+pub(crate) fn arch(pid: u32, _exe_path: &str) -> Option<String> {
+    let header = read_header(&format!("/proc/{}/exe", pid))?;
+    if !header.starts_with(b"\x7fELF") {
+        return None;
+    }
+    // e_machine, in the byte order EI_DATA (byte 5) gives: 1 little-endian, 2 big-endian
+    let machine = match header[5] {
+        2 => u16::from_be_bytes([header[18], header[19]]),
+        _ => u16::from_le_bytes([header[18], header[19]]),
+    };
+    let arch = match machine {
+        0x03 => "x86",
+        0x28 => "arm",
+        0x3e => "x86_64",
+        0xb7 => "arm64",
+        0xf3 => "riscv",
+        0x15 => "ppc64",
+        0x16 => "s390x",
+        other => return Some(format!("unknown ({:#x})", other)),
+    };
+    Some(arch.to_string())
+}
+
+/// Reads the architecture from the Mach-O header of `exe_path`. Fat binaries
+/// carry several and are reported as `"universal"`, even though only one slice
+/// is running.
+#[cfg(target_os = "macos")]
+pub(crate) fn arch(_pid: u32, exe_path: &str) -> Option<String> {
+    let header = read_header(exe_path)?;
+    let arch = match header[..4] {
+        // FAT_MAGIC and FAT_MAGIC_64 are stored big-endian
+        [0xca, 0xfe, 0xba, 0xbe] | [0xca, 0xfe, 0xba, 0xbf] => "universal",
+        // MH_MAGIC and MH_MAGIC_64, little-endian on every Mac; cputype follows
+        [0xce, 0xfa, 0xed, 0xfe] | [0xcf, 0xfa, 0xed, 0xfe] => {
+            match u32::from_le_bytes([header[4], header[5], header[6], header[7]]) {
+                0x0000_0007 => "x86",
+                0x0100_0007 => "x86_64",
+                0x0000_000c => "arm",
+                0x0100_000c => "arm64",
+                other => return Some(format!("unknown ({:#x})", other)),
+            }
+        }
+        _ => return None,
+    };
+    Some(arch.to_string())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(crate) fn arch(_pid: u32, _exe_path: &str) -> Option<String> {
+    None
+}
+
+/// Total (received, sent) bytes from `/proc/<pid>/net/dev`, skipping loopback.
+/// The counters belong to the network namespace, not the process: every process
+/// in the host namespace reports the host's totals, while a containerized one
+/// reports its container's.
+#[cfg(target_os = "linux")]
+pub(crate) fn net_io(pid: u32) -> Option<(u64, u64)> {
+    let dev = fs::read_to_string(format!("/proc/{}/net/dev", pid)).ok()?;
+    // Two header lines, then "  eth0: <8 receive counters> <8 transmit counters>"
+    let totals = dev.lines().skip(2).filter_map(|line| {
+        let (interface, counters) = line.split_once(':')?;
+        if interface.trim() == "lo" {
+            return None;
+        }
+        let counters: Vec<&str> = counters.split_whitespace().collect();
+        Some((counters.first()?.parse::<u64>().ok()?, counters.get(8)?.parse::<u64>().ok()?))
+    });
+    Some(totals.fold((0, 0), |(rx, tx), (line_rx, line_tx)| (rx + line_rx, tx + line_tx)))
+}
+
+/// Bytes `pid` has received and sent over its sockets, from one `nettop` sample.
+/// The `-x` CSV has a header row, then `<name>.<pid>,<bytes_in>,<bytes_out>,`.
+#[cfg(target_os = "macos")]
+pub(crate) fn net_io(pid: u32) -> Option<(u64, u64)> {
+    let output = std::process::Command::new("nettop")
+        .args(["-P", "-L", "1", "-x", "-J", "bytes_in,bytes_out", "-p", &pid.to_string()])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut rows = stdout.lines().skip(1).filter_map(|line| {
+        let mut fields = line.split(',').skip(1);
+        Some((fields.next()?.parse::<u64>().ok()?, fields.next()?.parse::<u64>().ok()?))
+    });
+    rows.next()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(crate) fn net_io(_pid: u32) -> Option<(u64, u64)> {
+    None
+}
+
+/// The first 20 bytes of `path`, enough for the ELF `e_machine` and Mach-O `cputype`.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn read_header(path: &str) -> Option<[u8; 20]> {
+    use std::io::Read;
+
+    let mut header = [0u8; 20];
+    std::fs::File::open(path).ok()?.read_exact(&mut header).ok()?;
+    Some(header)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scheduler_reads_policy_and_priority() {
+        let sshd = "812 (sshd) S 1 812 812 0 -1 4194560 2637 4385 2 4 13 7 12 9 20 0 1 0 1296 15949824 1893 \
+                    18446744073709551615 94366755852288 94366756677701 140722935051312 0 0 0 0 4096 81925 0 0 0 17 1 \
+                    0 0 0 0 0 94366756923664 94366756946112 94366779060224 140722935054010 140722935054035 \
+                    140722935054035 140722935054311 0";
+        let other = parse_scheduler(sshd).unwrap();
+        assert_eq!((other.policy.as_str(), other.priority), ("SCHED_OTHER", 0));
+
+        let migration = "14 (migration/0) S 2 0 0 0 -1 69238848 0 0 0 0 0 3 0 0 -100 0 1 0 3 0 0 \
+                         18446744073709551615 0 0 0 0 0 0 0 2147483647 0 0 0 0 17 0 99 1 0 0 0 0 0 0 0 0 0 0 0";
+        let fifo = parse_scheduler(migration).unwrap();
+        assert_eq!((fifo.policy.as_str(), fifo.priority), ("SCHED_FIFO", 99));
+
+        assert!(parse_scheduler("14 (migration/0) S 2 0 0").is_err());
+    }
+
+    #[test]
+    fn parse_cpu_list_expands_ranges() {
+        assert_eq!(parse_cpu_list("0-3,7"), Some(vec![0, 1, 2, 3, 7]));
+        assert_eq!(parse_cpu_list("0"), Some(vec![0]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("3-"), None);
+        assert_eq!(parse_cpu_list("0,x"), None);
+    }
+
+    #[test]
+    fn cgroup_container_id_finds_docker_paths() {
+        let id = "4f8e1c2d9b7a6e5f3c2b1a0d9e8f7c6b5a4d3e2f1c0b9a8e7d6c5b4a3f2e1d0c";
+        let v1 = format!("12:memory:/docker/{}", id);
+        let systemd = format!("0::/system.slice/docker-{}.scope", id);
+        let containers = format!("0::/kubepods/besteffort/pod1234/containers/{}", id);
+
+        assert_eq!(cgroup_container_id(&v1).as_deref(), Some(id));
+        assert_eq!(cgroup_container_id(&systemd).as_deref(), Some(id));
+        assert_eq!(cgroup_container_id(&containers).as_deref(), Some(id));
+        // Short IDs, such as the 12-character form `docker ps` shows, aren't container cgroups
+        assert_eq!(cgroup_container_id("12:memory:/docker/4f8e1c2d9b7a"), None);
+        assert_eq!(cgroup_container_id("0::/system.slice/docker.service"), None);
+        assert_eq!(cgroup_container_id("0::/user.slice/user-1000.slice/session-2.scope"), None);
+    }
+
+    #[test]
+    fn parse_pmap_row_skips_everything_but_mappings() {
+        let stdout = "\
+4242:   nginx: master process /usr/sbin/nginx
+Address           Kbytes     RSS   Dirty Mode  Mapping
+0000559a3c400000     168     168       0 r---- nginx
+0000559a3d8e1000     780     644     644 rw---   [ anon ]
+00007f2b4c028000    1620       -       - r-x-- libc.so.6
+---------------- ------- ------- -------
+total kB           10408    5620    1200
+";
+        let regions: Vec<_> = stdout
+            .lines()
+            .filter_map(parse_pmap_row)
+            .map(|r| (r.address, r.size_kb, r.rss_kb, r.dirty_kb, r.mapping))
+            .collect();
+        assert_eq!(
+            regions,
+            [
+                ("0000559a3c400000".to_string(), 168, 168, Some(0), "nginx".to_string()),
+                ("0000559a3d8e1000".to_string(), 780, 644, Some(644), "[ anon ]".to_string()),
+                ("00007f2b4c028000".to_string(), 1620, 0, None, "libc.so.6".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn shared_libraries_merge_the_segments_of_each_object() {
+        let maps = "\
+559a3c400000-559a3c42a000 r--p 00000000 fd:01 1048712                    /usr/sbin/nginx
+559a3d8e1000-559a3d9a4000 rw-p 00000000 00:00 0                          [heap]
+7f2b4c000000-7f2b4c028000 r--p 00000000 fd:01 1054003                    /usr/lib/x86_64-linux-gnu/libc.so.6
+7f2b4c028000-7f2b4c1bd000 r-xp 00028000 fd:01 1054003                    /usr/lib/x86_64-linux-gnu/libc.so.6
+7f2b4c1bd000-7f2b4c215000 r--p 001bd000 fd:01 1054003                    /usr/lib/x86_64-linux-gnu/libc.so.6
+7f2b4c215000-7f2b4c219000 rw-p 00000000 00:00 0
+7f2b4c300000-7f2b4c305000 r-xp 00000000 fd:01 1311562                    /opt/My App/lib/libplugin.so
+7ffd1e5a0000-7ffd1e5c1000 rw-p 00000000 00:00 0                          [stack]
+";
+        let libraries = merge_mappings(maps.lines().filter_map(maps_library));
+        let summary: Vec<_> = libraries
+            .iter()
+            .map(|lib| (lib.path.as_str(), lib.base_address.as_str(), lib.size_kb))
+            .collect();
+        // libc: 0x28000 + 0x195000 + 0x58000 bytes
+        assert_eq!(
+            summary,
+            [
+                ("/usr/lib/x86_64-linux-gnu/libc.so.6", "0x7f2b4c000000", 2132),
+                ("/opt/My App/lib/libplugin.so", "0x7f2b4c300000", 20),
+            ]
+        );
+    }
+}
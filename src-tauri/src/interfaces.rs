@@ -0,0 +1,230 @@
+//! Network interfaces and their addresses, for telling which interface a bind
+//! address like `192.168.1.10` belongs to. Read from `ip addr` on Linux, where
+//! `ifconfig` is often not installed, and from `ifconfig -a` elsewhere.
+
+use super::NetworkInterface;
+use std::io;
+use std::process::Command;
+
+/// Lists every interface, up or down, in the order the tool prints them.
+pub(crate) fn list() -> Result<Vec<NetworkInterface>, String> {
+    #[cfg(target_os = "linux")]
+    match run("ip", &["addr"]) {
+        Ok(stdout) => return Ok(parse_ip_addr(&stdout)),
+        // Distributions that still ship only net-tools
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to execute ip: {}", e)),
+    }
+
+    let stdout = run("ifconfig", &["-a"]).map_err(|e| format!("Failed to execute ifconfig: {}", e))?;
+    Ok(parse_ifconfig(&stdout))
+}
+
+fn run(tool: &str, args: &[&str]) -> io::Result<String> {
+    let output = Command::new(tool).args(args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A new interface from its name and the flags between `<` and `>`, e.g.
+/// `LOOPBACK,UP,LOWER_UP`.
+fn interface(name: &str, flags: &str) -> NetworkInterface {
+    let has_flag = |flag: &str| flags.split(',').any(|f| f == flag);
+    NetworkInterface {
+        name: name.to_string(),
+        ipv4_addresses: Vec::new(),
+        ipv6_addresses: Vec::new(),
+        mac_address: String::new(),
+        is_up: has_flag("UP"),
+        is_loopback: has_flag("LOOPBACK"),
+    }
+}
+
+/// The text between the first `<` and the following `>`.
+fn flags_of(line: &str) -> &str {
+    line.split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map_or("", |(flags, _)| flags)
+}
+
+/// Parses `ip addr` output:
+///
+/// ```text
+/// 2: eth0@if5: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc noqueue state UP
+///     link/ether 02:42:ac:11:00:02 brd ff:ff:ff:ff:ff:ff link-netnsid 0
+///     inet 172.17.0.2/16 brd 172.17.255.255 scope global eth0
+///        valid_lft forever preferred_lft forever
+///     inet6 fe80::42:acff:fe11:2/64 scope link
+/// ```
+///
+/// Addresses are kept without their prefix length, the way `lsof` prints
+/// bind addresses, and names without the `@ifN` peer suffix of veth pairs.
+#[cfg(target_os = "linux")]
+fn parse_ip_addr(stdout: &str) -> Vec<NetworkInterface> {
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+
+    for line in stdout.lines() {
+        if !line.starts_with(' ') {
+            // "<index>: <name>: <flags> ..."
+            let mut parts = line.splitn(3, ": ");
+            if let (Some(_), Some(name)) = (parts.next(), parts.next()) {
+                let name = name.split('@').next().unwrap_or(name);
+                interfaces.push(interface(name, flags_of(line)));
+            }
+            continue;
+        }
+
+        let Some(current) = interfaces.last_mut() else {
+            continue;
+        };
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("inet"), Some(address)) => current.ipv4_addresses.push(strip_prefix_len(address)),
+            (Some("inet6"), Some(address)) => current.ipv6_addresses.push(strip_prefix_len(address)),
+            // Loopback has an all-zero address that ifconfig doesn't print
+            (Some("link/loopback"), _) => {}
+            // link/ether and the like; tunnels have link/none with no address
+            (Some(kind), Some(address)) if kind.starts_with("link/") && address.contains(':') => {
+                current.mac_address = address.to_string();
+            }
+            _ => {}
+        }
+    }
+
+    interfaces
+}
+
+#[cfg(target_os = "linux")]
+fn strip_prefix_len(address: &str) -> String {
+    address.split('/').next().unwrap_or(address).to_string()
+}
+
+/// Parses BSD and net-tools `ifconfig -a` output:
+///
+/// ```text
+/// en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+///     ether a4:83:e7:12:34:56
+///     inet6 fe80::1c2b:3d4e:5f60:7182%en0 prefixlen 64 secured scopeid 0x6
+///     inet 192.168.1.10 netmask 0xffffff00 broadcast 192.168.1.255
+/// ```
+///
+/// Scope suffixes such as `%en0` are dropped from IPv6 addresses. The old
+/// net-tools layout (`Link encap:`, `inet addr:`) isn't understood.
+fn parse_ifconfig(stdout: &str) -> Vec<NetworkInterface> {
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+
+    for line in stdout.lines() {
+        if !line.starts_with([' ', '\t']) {
+            if let Some((name, _)) = line.split_once(": ") {
+                interfaces.push(interface(name, flags_of(line)));
+            }
+            continue;
+        }
+
+        let Some(current) = interfaces.last_mut() else {
+            continue;
+        };
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("inet"), Some(address)) => current.ipv4_addresses.push(address.to_string()),
+            (Some("inet6"), Some(address)) => {
+                let address = address.split('%').next().unwrap_or(address);
+                current.ipv6_addresses.push(address.to_string());
+            }
+            // "lladdr" on OpenBSD
+            (Some("ether" | "lladdr"), Some(address)) => current.mac_address = address.to_string(),
+            _ => {}
+        }
+    }
+
+    interfaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected(
+        name: &str,
+        ipv4: &[&str],
+        ipv6: &[&str],
+        mac: &str,
+        is_up: bool,
+        is_loopback: bool,
+    ) -> NetworkInterface {
+        NetworkInterface {
+            name: name.to_string(),
+            ipv4_addresses: ipv4.iter().map(|a| a.to_string()).collect(),
+            ipv6_addresses: ipv6.iter().map(|a| a.to_string()).collect(),
+            mac_address: mac.to_string(),
+            is_up,
+            is_loopback,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_ip_addr_reads_addresses_and_flags() {
+        let stdout = "\
+1: lo: <LOOPBACK,UP,LOWER_UP> mtu 65536 qdisc noqueue state UNKNOWN group default qlen 1000
+    link/loopback 00:00:00:00:00:00 brd 00:00:00:00:00:00
+    inet 127.0.0.1/8 scope host lo
+       valid_lft forever preferred_lft forever
+    inet6 ::1/128 scope host
+       valid_lft forever preferred_lft forever
+2: eth0@if5: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc noqueue state UP group default
+    link/ether 02:42:ac:11:00:02 brd ff:ff:ff:ff:ff:ff link-netnsid 0
+    inet 172.17.0.2/16 brd 172.17.255.255 scope global eth0
+       valid_lft forever preferred_lft forever
+    inet6 fe80::42:acff:fe11:2/64 scope link
+       valid_lft forever preferred_lft forever
+3: tun0: <POINTOPOINT,MULTICAST,NOARP> mtu 1500 qdisc noop state DOWN group default qlen 500
+    link/none
+";
+        assert_eq!(
+            parse_ip_addr(stdout),
+            [
+                expected("lo", &["127.0.0.1"], &["::1"], "", true, true),
+                expected("eth0", &["172.17.0.2"], &["fe80::42:acff:fe11:2"], "02:42:ac:11:00:02", true, false),
+                expected("tun0", &[], &[], "", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_ifconfig_reads_bsd_output() {
+        let stdout = "\
+lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384
+\toptions=1203<RXCSUM,TXCSUM,TXSTATUS,SW_TIMESTAMP>
+\tinet 127.0.0.1 netmask 0xff000000
+\tinet6 ::1 prefixlen 128
+\tinet6 fe80::1%lo0 prefixlen 64 scopeid 0x1
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tether a4:83:e7:12:34:56
+\tinet6 fe80::1c2b:3d4e:5f60:7182%en0 prefixlen 64 secured scopeid 0x6
+\tinet 192.168.1.10 netmask 0xffffff00 broadcast 192.168.1.255
+\tstatus: active
+en1: flags=8822<BROADCAST,SMART,SIMPLEX,MULTICAST> mtu 1500
+\tether 3a:1f:27:a0:b1:c2
+\tstatus: inactive
+";
+        assert_eq!(
+            parse_ifconfig(stdout),
+            [
+                expected("lo0", &["127.0.0.1"], &["::1", "fe80::1"], "", true, true),
+                expected(
+                    "en0",
+                    &["192.168.1.10"],
+                    &["fe80::1c2b:3d4e:5f60:7182"],
+                    "a4:83:e7:12:34:56",
+                    true,
+                    false
+                ),
+                expected("en1", &[], &[], "3a:1f:27:a0:b1:c2", false, false),
+            ]
+        );
+    }
+}
@@ -1,49 +1,930 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
 use std::process::Command;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+mod api;
+mod audit;
+mod config;
+#[cfg(target_os = "linux")]
+mod cgroup;
+mod ephemeral;
+mod error;
+mod export;
+#[cfg(target_os = "freebsd")]
+mod freebsd;
+mod inspect;
+#[cfg(not(target_os = "windows"))]
+mod interfaces;
+mod monitor;
+#[cfg(target_os = "linux")]
+mod procfs;
+mod provider;
+#[cfg(target_os = "macos")]
+mod security;
+mod services;
+mod state;
+mod system;
+#[cfg(not(target_os = "windows"))]
+mod tools;
+mod watchlist;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use api::ApiServerState;
+use audit::KillLogEntry;
+use config::{Config, ConfigState};
+use error::{KillError, ListPortsError};
+use monitor::{Backoff, MonitorState, MonitorStatus, PortWatchState, ThresholdWatchState};
+use provider::{DynProvider, ProcessInfoProvider};
+use state::AppState;
+#[cfg(target_os = "windows")]
+use windows::{deliver_signal, is_process_alive};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct PidInfo {
     pid: u32,
-    ports: String,
+    protocol: String,
+    /// Every socket family the PID has in the listing, e.g. `["TCP4", "TCP6"]` for a
+    /// dual-stack listener, sorted. Unlike `protocol`, IPv4 is spelled out.
+    protocols: Vec<String>,
+    state: String,
+    /// Local port numbers, in ascending order.
+    ports: Vec<u16>,
+    /// Local address the ports are bound to, e.g. `"*"` or `"0.0.0.0"` (all interfaces),
+    /// `"127.0.0.1"` or `"::1"` (loopback only). IPv6 addresses are given without brackets.
+    bind_address: String,
+    /// Service-labelled ports such as `"postgres (5432)"`; only filled by `list_ports_with_labels`.
+    port_labels: Vec<String>,
+    /// Service names of the ports that have one, in port order, e.g. `["http", "https"]`.
+    service_names: Vec<String>,
+    /// Whether every port is in the OS's ephemeral range, i.e. probably the local
+    /// end of an outgoing connection rather than a service. False without ports.
+    is_ephemeral: bool,
     user: String,
-    cpu: String,
-    mem: String,
+    /// Numeric user ID, which unlike `user` can't be a renamed or unresolved
+    /// account. Null when unavailable (e.g. on Windows).
+    uid: Option<u32>,
+    /// Whether the process runs as UID 0.
+    is_root: bool,
+    /// Cumulative bytes received and sent, or 0 when unknown. On Linux these are
+    /// the totals of the process's network namespace (loopback excluded), so a
+    /// process on the host reports the host's traffic. macOS counts the process's
+    /// own sockets but needs a `nettop` sample, so only `get_process_detail` fills them.
+    net_rx_bytes: u64,
+    net_tx_bytes: u64,
+    /// `%cpu` as reported by `ps`, or null when unavailable (e.g. on Windows).
+    cpu: Option<f32>,
+    /// Cumulative user + system CPU time as `ps` prints it: `[dd-]hh:mm:ss` on
+    /// Linux, `mm:ss.ss` on macOS. Empty when unknown.
+    cpu_time: String,
+    /// `cpu_time` in whole seconds, or 0 when unknown or reported as `-`.
+    cpu_time_secs: u64,
+    /// `%mem` as reported by `ps`, or null when unavailable. Deprecated: a share
+    /// of physical memory can't be compared across machines; use `rss_kb`.
+    mem: Option<f32>,
+    /// Virtual memory size in KiB, or 0 when unknown (always on Windows).
+    vsz_kb: u64,
+    /// Resident set size in KiB (the working set on Windows), or 0 when unknown.
+    rss_kb: u64,
+    start_time: String,
+    /// Seconds since the process started, or 0 when unknown.
+    uptime_seconds: u64,
+    /// Primary `ps` state code: R (running), S (sleeping), D (disk wait), T (stopped) or Z (zombie).
+    process_state: String,
+    ppid: u32,
+    /// Process group ID, for `kill_process_group`.
+    pgid: u32,
+    /// Session ID, for `kill_session`. Only reported on Linux; 0 elsewhere.
+    sid: u32,
+    /// Scheduling niceness from -20 (highest priority) to 19, or 0 when `ps` omits it.
+    nice: i8,
+    /// Thread count, or 0 when the platform's `ps` can't report it. macOS needs
+    /// a `ps -M` per process, so there only `get_process_detail` counts them.
+    threads: u32,
+    /// Working directory, or empty when it can't be read (e.g. another user's process).
+    /// Like `fd_count`, `exe_path` and `arch`, only filled by `get_process_detail`.
+    cwd: String,
+    /// Open file descriptors, or 0 when they can't be counted.
+    fd_count: u32,
+    /// Resolved executable, unlike `command` which is the full argv string. Empty if unavailable.
+    exe_path: String,
+    /// CPU architecture of the executable: `"x86_64"`, `"arm64"`, `"universal"` (a fat
+    /// macOS binary) and so on. None when `exe_path` is unknown or unreadable.
+    arch: Option<String>,
+    /// Linux OOM killer badness (0-1000, higher is killed first); None elsewhere.
+    oom_score: Option<i32>,
+    /// Adjustment added to `oom_score`, from -1000 (never kill) to 1000; None off Linux.
+    oom_score_adj: Option<i32>,
+    /// Full 64-character ID of the Docker container the process runs in. Only
+    /// detected on Linux; on macOS containers live inside Docker Desktop's VM.
+    container_id: Option<String>,
+    /// systemd service managing the process, e.g. `"nginx.service"`. None off
+    /// systemd, including on macOS.
+    systemd_unit: Option<String>,
+    /// cgroup v2 path such as `"/system.slice/nginx.service"`, for
+    /// `get_cgroup_stats`. Linux only; None on systems with only cgroup v1.
+    cgroup_path: Option<String>,
+    /// launchd job label on macOS, e.g. `"com.apple.mDNSResponder"`. Only looked up
+    /// by `get_process_detail`, since `launchctl` is too slow for every listing.
+    launchd_label: Option<String>,
+    /// Whether the executable has a valid code signature (macOS). Like
+    /// `signing_authority`, only looked up by `get_process_detail`.
+    is_signed: Option<bool>,
+    /// Leaf certificate the executable is signed with, e.g. `"Software Signing"`.
+    signing_authority: Option<String>,
+    /// Values of the extra `ps` columns added with `set_ps_format`, keyed
+    /// by column name (e.g. `"tty"`). Empty unless some were added.
+    #[serde(default)]
+    ps_extra: HashMap<String, String>,
+}
+
+impl PidInfo {
+    fn from_details(
+        pid: u32,
+        protocol: String,
+        state: String,
+        ports: Vec<u16>,
+        bind_address: String,
+        details: ProcessDetails,
+    ) -> Self {
+        PidInfo {
+            pid,
+            protocol,
+            protocols: Vec::new(),
+            state,
+            is_ephemeral: !ports.is_empty() && ports.iter().all(|&port| ephemeral::contains(port)),
+            service_names: ports.iter().filter_map(|&port| services::port_to_service_name(port)).collect(),
+            ports,
+            bind_address,
+            port_labels: Vec::new(),
+            user: details.user,
+            uid: details.uid,
+            is_root: details.uid == Some(0),
+            net_rx_bytes: details.net_rx_bytes,
+            net_tx_bytes: details.net_tx_bytes,
+            cpu: details.cpu,
+            cpu_time_secs: details.cpu_time_secs,
+            cpu_time: details.cpu_time,
+            mem: details.mem,
+            vsz_kb: details.vsz_kb,
+            rss_kb: details.rss_kb,
+            start_time: details.start_time,
+            uptime_seconds: details.uptime_seconds,
+            process_state: details.process_state,
+            ppid: details.ppid,
+            pgid: details.pgid,
+            sid: details.sid,
+            nice: details.nice,
+            threads: details.threads,
+            cwd: details.cwd,
+            fd_count: details.fd_count,
+            exe_path: details.exe_path,
+            arch: details.arch,
+            oom_score: details.oom_score,
+            oom_score_adj: details.oom_score_adj,
+            container_id: details.container_id,
+            systemd_unit: details.systemd_unit,
+            cgroup_path: details.cgroup_path,
+            launchd_label: None,
+            is_signed: None,
+            signing_authority: None,
+            ps_extra: details.ps_extra,
+        }
+    }
+
+    /// The fields `PortsDiff` compares. Counters such as `uptime_seconds`,
+    /// `cpu_time` and `net_rx_bytes` grow between any two listings, so
+    /// comparing whole entries would report every group as changed.
+    fn diff_key(&self) -> (u32, &[u16], &str, &str, &str) {
+        (self.pid, &self.ports, &self.protocol, &self.state, &self.user)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct PortInfo {
     process_name: String,
     command: String,
     pids: Vec<PidInfo>,
+    /// Connected sockets (e.g. ESTABLISHED, TIME_WAIT) across the group's PIDs, as a
+    /// load indicator. Filled by the listening-port listings such as `list_ports`;
+    /// 0 elsewhere, when counting fails, and on Windows.
+    connection_count: u32,
 }
 
-#[tauri::command]
-fn list_ports() -> Result<Vec<PortInfo>, String> {
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] list_ports command called");
+/// An open UNIX domain socket, from `lsof -U`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct UnixSocketInfo {
+    process_name: String,
+    pid: u32,
+    user: String,
+    /// Filesystem path, `@name` for Linux abstract sockets, or `"(anonymous)"`.
+    socket_path: String,
+    /// `"STREAM"`, `"DGRAM"` or `"SEQPACKET"`; empty where `lsof` doesn't report it (macOS).
+    socket_type: String,
+}
 
-    // Use -sTCP:LISTEN to only show listening ports (servers), not outbound connections
-    let output = Command::new("lsof")
-        .args(["-i", "-P", "-n", "-sTCP:LISTEN"])
-        .output()
-        .map_err(|e| {
-            #[cfg(debug_assertions)]
-            println!("[DEBUG] Failed to execute lsof: {}", e);
-            format!("Failed to execute lsof: {}", e)
-        })?;
+/// A process that has exited but not yet been reaped by its parent.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ZombieInfo {
+    pid: u32,
+    process_name: String,
+    /// The parent that hasn't called `wait()`; see `kill_zombie_parent`.
+    ppid: u32,
+    user: String,
+}
+
+/// A process and its descendants, from `get_descendant_tree`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ProcessTree {
+    node: PidInfo,
+    /// In the order `ps` lists them.
+    children: Vec<ProcessTree>,
+}
+
+/// A network interface from `list_network_interfaces`, for matching the
+/// `bind_address` of a `PidInfo` to the interface it belongs to.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct NetworkInterface {
+    /// e.g. `"eth0"`, `"en0"`, `"lo"`.
+    name: String,
+    ipv4_addresses: Vec<String>,
+    /// Without prefix length or `%scope` suffix.
+    ipv6_addresses: Vec<String>,
+    /// Empty for interfaces without one, such as tunnels.
+    mac_address: String,
+    is_up: bool,
+    is_loopback: bool,
+}
+
+/// Result of `check_process_health`. Each step is checked even if an earlier one failed.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct HealthStatus {
+    pid_exists: bool,
+    /// Whether the PID has a TCP socket listening on the port.
+    port_open: bool,
+    /// Whether a TCP connection to the port was accepted within the timeout.
+    tcp_connectable: bool,
+    /// How long the connection took; None when it failed.
+    latency_ms: Option<u64>,
+}
+
+/// One step of the chain `get_process_ancestors` walks from a process up to init.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AncestorInfo {
+    pid: u32,
+    process_name: String,
+    /// Empty for kernel threads and processes we may not inspect.
+    command: String,
+}
+
+/// One open file descriptor of a process, from `lsof -p`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct OpenFileInfo {
+    /// Descriptor number, or a special name such as `"cwd"`, `"txt"` or `"mem"`.
+    fd: String,
+    /// `"REG"`, `"DIR"`, `"CHR"`, `"FIFO"`, `"unix"`, `"IPv4"` and so on.
+    #[serde(rename = "type")]
+    type_: String,
+    /// Device number as `lsof` reports it in field output, e.g. `"0x10300"`.
+    device: String,
+    /// Bytes; 0 for descriptors without a size, such as sockets and pipes.
+    size: u64,
+    /// Inode number, or empty when there is none.
+    node: String,
+    name: String,
+}
+
+/// One internet socket of a process, listening or connected.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ConnectionInfo {
+    /// `host:port`, e.g. `"127.0.0.1:5432"` or `"[::1]:443"`.
+    local_addr: String,
+    /// `host:port` of the peer, or `"*"` for listening and unconnected sockets.
+    remote_addr: String,
+    /// `"TCP"`, `"TCP6"`, `"UDP"` or `"UDP6"`, as in `PidInfo.protocol`.
+    protocol: String,
+    /// TCP state such as `"ESTABLISHED"`; empty for UDP.
+    state: String,
+}
+
+/// The listening processes that belong to one Docker container.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ContainerInfo {
+    container_id: String,
+    /// Ascending.
+    pids: Vec<u32>,
+    /// Sorted, one per distinct name.
+    process_names: Vec<String>,
+    /// Every port the container's processes listen on, ascending.
+    ports: Vec<u16>,
+}
+
+/// Network throughput of a process over a sampling window, from `get_network_io_rate`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct NetworkIORate {
+    pid: u32,
+    window_ms: u64,
+    rx_bytes_per_sec: f64,
+    tx_bytes_per_sec: f64,
+}
+
+/// Code signing and sandbox status of a process's executable, from `get_process_security`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ProcessSecurity {
+    pid: u32,
+    exe_path: String,
+    /// None if `codesign` couldn't be run.
+    is_signed: Option<bool>,
+    /// Leaf signing certificate; None for unsigned and ad-hoc signed code.
+    signing_authority: Option<String>,
+    /// Whether the executable is entitled to (and so runs in) the App Sandbox.
+    sandbox_enabled: bool,
+}
+
+/// Machine-wide load and memory, for the summary card above the process list.
+#[derive(Debug, Serialize, Clone)]
+pub struct SystemOverview {
+    cpu_count: u32,
+    load_avg_1m: f64,
+    load_avg_5m: f64,
+    load_avg_15m: f64,
+    total_memory_kb: u64,
+    used_memory_kb: u64,
+    /// Memory available to new processes without swapping.
+    free_memory_kb: u64,
+}
+
+/// Changes between two listings, keyed by (process_name, command) like the grouping itself.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PortsDiff {
+    /// Groups that are new since the previous listing.
+    added: Vec<PortInfo>,
+    /// Groups that are gone, as they were in the previous listing.
+    removed: Vec<PortInfo>,
+    /// Groups present in both whose PIDs, or their ports, protocol, socket
+    /// state or user, differ, as they are now.
+    changed: Vec<PortInfo>,
+}
+
+/// What `list_ports` returns: the listing, and the ID the call was given so
+/// concurrent callers can tell responses apart. A `busy` error names the same ID.
+#[derive(Debug, Serialize, Clone)]
+pub struct PortListing {
+    request_id: u64,
+    ports: Vec<PortInfo>,
+}
+
+impl PortsDiff {
+    fn between(previous: &[PortInfo], current: &[PortInfo]) -> Self {
+        let key = |p: &PortInfo| (p.process_name.clone(), p.command.clone());
+        let before: HashMap<(String, String), &PortInfo> = previous.iter().map(|p| (key(p), p)).collect();
+        let after: HashSet<(String, String)> = current.iter().map(key).collect();
+
+        let same_pids = |a: &PortInfo, b: &PortInfo| {
+            a.pids.iter().map(PidInfo::diff_key).eq(b.pids.iter().map(PidInfo::diff_key))
+        };
+
+        let mut diff = PortsDiff::default();
+        for port_info in current {
+            match before.get(&key(port_info)) {
+                None => diff.added.push(port_info.clone()),
+                Some(&old) if !same_pids(old, port_info) => diff.changed.push(port_info.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = previous.iter().filter(|p| !after.contains(&key(p))).cloned().collect();
+        diff
+    }
+}
+
+/// Summary of a listing from `get_port_statistics`. Counts are of distinct
+/// (protocol, bind address, port) bindings, so a socket shared by several
+/// PIDs (e.g. prefork workers) counts once.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PortStatistics {
+    total_listening_ports: u32,
+    tcp4_count: u32,
+    tcp6_count: u32,
+    /// UDP over both IPv4 and IPv6.
+    udp_count: u32,
+    /// Bindings held by each user's processes; a binding shared between users counts for each.
+    by_user: HashMap<String, u32>,
+    /// Keyed by `PidInfo.protocol`: `"TCP"`, `"TCP6"`, `"UDP"`, `"UDP6"`.
+    by_protocol: HashMap<String, u32>,
+    /// The IANA range most bindings fall in: `"well-known"` (below 1024),
+    /// `"registered"` (1024-49151) or `"ephemeral"` (above 49151). Ties go to
+    /// the lower range; empty when nothing is listening.
+    most_used_port_range: String,
+}
+
+impl PortStatistics {
+    fn from_ports(ports: &[PortInfo]) -> Self {
+        let mut bindings: HashSet<(&str, &str, u16)> = HashSet::new();
+        let mut user_bindings: HashMap<&str, HashSet<(&str, &str, u16)>> = HashMap::new();
+        for pid_info in ports.iter().flat_map(|p| &p.pids) {
+            for &port in &pid_info.ports {
+                let binding = (pid_info.protocol.as_str(), pid_info.bind_address.as_str(), port);
+                bindings.insert(binding);
+                user_bindings.entry(&pid_info.user).or_default().insert(binding);
+            }
+        }
+
+        let mut stats = PortStatistics {
+            total_listening_ports: bindings.len() as u32,
+            by_user: user_bindings
+                .into_iter()
+                .map(|(user, bindings)| (user.to_string(), bindings.len() as u32))
+                .collect(),
+            ..Default::default()
+        };
+        // (well-known, registered, ephemeral)
+        let mut ranges = (0, 0, 0);
+        for &(protocol, _, port) in &bindings {
+            *stats.by_protocol.entry(protocol.to_string()).or_default() += 1;
+            match protocol {
+                "TCP" => stats.tcp4_count += 1,
+                "TCP6" => stats.tcp6_count += 1,
+                _ => stats.udp_count += 1,
+            }
+            match port {
+                0..=1023 => ranges.0 += 1,
+                1024..=49151 => ranges.1 += 1,
+                _ => ranges.2 += 1,
+            }
+        }
+        stats.most_used_port_range = match ranges {
+            (0, 0, 0) => "",
+            (well_known, registered, ephemeral) if well_known >= registered && well_known >= ephemeral => "well-known",
+            (_, registered, ephemeral) if registered >= ephemeral => "registered",
+            _ => "ephemeral",
+        }
+        .to_string();
+        stats
+    }
+}
+
+/// A listing saved by `take_snapshot`.
+#[derive(Debug, Serialize, Clone)]
+pub struct SnapshotMeta {
+    /// UUID to pass to `compare_snapshots`.
+    id: String,
+    /// UTC, RFC 3339.
+    taken_at: String,
+    /// Number of process groups in the listing.
+    group_count: usize,
+}
+
+/// Differences between two saved listings, matched by PID rather than by
+/// group, so a restarted process shows up as gone and new.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SnapshotDiff {
+    /// Groups in the later snapshot, trimmed to the PIDs the earlier one lacks.
+    new_processes: Vec<PortInfo>,
+    /// Groups in the earlier snapshot, trimmed to the PIDs the later one lacks.
+    gone_processes: Vec<PortInfo>,
+    /// (before, after) for PIDs in both snapshots whose details differ.
+    changed_pids: Vec<(PidInfo, PidInfo)>,
+}
+
+impl SnapshotDiff {
+    fn between(before: &[PortInfo], after: &[PortInfo]) -> Self {
+        let pids = |ports: &[PortInfo]| -> HashMap<u32, PidInfo> {
+            ports.iter().flat_map(|p| &p.pids).map(|pid_info| (pid_info.pid, pid_info.clone())).collect()
+        };
+        let (pids_before, pids_after) = (pids(before), pids(after));
+
+        // The groups of `ports` cut down to PIDs `other` doesn't have, dropping emptied groups
+        let only_in = |ports: &[PortInfo], other: &HashMap<u32, PidInfo>| -> Vec<PortInfo> {
+            ports
+                .iter()
+                .filter_map(|port_info| {
+                    let mut port_info = port_info.clone();
+                    port_info.pids.retain(|pid_info| !other.contains_key(&pid_info.pid));
+                    (!port_info.pids.is_empty()).then_some(port_info)
+                })
+                .collect()
+        };
+
+        let mut changed_pids: Vec<(PidInfo, PidInfo)> = pids_after
+            .values()
+            .filter_map(|new| {
+                let old = pids_before.get(&new.pid)?;
+                (old != new).then(|| (old.clone(), new.clone()))
+            })
+            .collect();
+        changed_pids.sort_by_key(|(old, _)| old.pid);
+
+        SnapshotDiff {
+            new_processes: only_in(after, &pids_before),
+            gone_processes: only_in(before, &pids_after),
+            changed_pids,
+        }
+    }
+}
+
+/// One page of a longer listing, for virtual scrolling in the frontend.
+#[derive(Debug, Serialize, Clone)]
+pub struct PagedResult<T> {
+    items: Vec<T>,
+    /// Length of the whole listing, not of this page.
+    total: usize,
+    offset: usize,
+    limit: usize,
+}
+
+/// Bytes a process has caused to be read from and written to storage. Unlike
+/// the totals of every `read`/`write` call, these leave out page cache hits.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DiskIO {
+    read_bytes: u64,
+    write_bytes: u64,
+    /// Writes that were accounted but never happened, e.g. because the file
+    /// was truncated while its pages were still dirty.
+    cancelled_write_bytes: u64,
+}
+
+/// One mapping (Linux) or one region type (macOS) in a process's address space.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MemoryRegion {
+    /// Start address in hex; empty on macOS, where regions are summarized by type.
+    address: String,
+    size_kb: u64,
+    rss_kb: u64,
+    /// None when the tool couldn't account the region.
+    dirty_kb: Option<u64>,
+    /// Mapped file, `[ anon ]`/`[ stack ]` on Linux, or the region type on macOS.
+    mapping: String,
+}
+
+/// The external binaries the backend depends on, from `check_dependencies`.
+#[derive(Debug, Serialize, Clone)]
+pub struct DependencyStatus {
+    /// Whether every tool listing and killing can't do without was found.
+    all_required_found: bool,
+    tools: Vec<ToolStatus>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolStatus {
+    name: String,
+    /// Whether listing or killing fails without it, rather than a single command.
+    required: bool,
+    /// Resolved location; None if it wasn't found.
+    path: Option<String>,
+    /// None when found but it has no way to report one (e.g. BSD `ps`).
+    version: Option<String>,
+    /// How to install it, for the tools that have a known package.
+    install_hint: Option<String>,
+}
+
+/// Resource usage of a cgroup v2 group, from `get_cgroup_stats`. Each figure is
+/// None when its controller isn't enabled for the group.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CgroupStats {
+    cgroup_path: String,
+    memory_current_bytes: Option<u64>,
+    /// The group's memory limit; None when unlimited.
+    memory_max_bytes: Option<u64>,
+    cpu_usage_usec: Option<u64>,
+    cpu_user_usec: Option<u64>,
+    cpu_system_usec: Option<u64>,
+    /// How many periods the group hit its `cpu.max` quota in, and for how long in total.
+    cpu_nr_throttled: Option<u64>,
+    cpu_throttled_usec: Option<u64>,
+    /// Summed over every device.
+    io_read_bytes: Option<u64>,
+    io_write_bytes: Option<u64>,
+}
+
+/// How the kernel schedules a process, from `get_scheduler_info`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SchedulerInfo {
+    /// `"SCHED_OTHER"`, `"SCHED_FIFO"`, `"SCHED_RR"`, `"SCHED_BATCH"`, `"SCHED_IDLE"`
+    /// or `"SCHED_DEADLINE"`. FIFO and RR are real-time and can starve everything else.
+    policy: String,
+    /// Real-time priority from 1 to 99 for FIFO and RR; 0 for the other policies.
+    priority: i32,
+}
+
+/// A shared library loaded into a process, from `get_shared_libraries`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SharedLib {
+    path: String,
+    /// Lowest address any of the library's segments is mapped at, e.g. `"0x7f3a1c000000"`.
+    base_address: String,
+    /// Total size of the library's mapped segments.
+    size_kb: u64,
+}
+
+/// Resource limits of a process, as `ulimit` would report them from inside it:
+/// soft limits, plus the hard ones for open files and processes. `None` means
+/// unlimited (or, where a limit can't be read, unknown).
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ResourceLimits {
+    max_open_files: Option<u64>,
+    max_open_files_hard: Option<u64>,
+    /// Resident set size in bytes (`ulimit -m`).
+    max_memory_size: Option<u64>,
+    /// Address space in bytes (`ulimit -v`).
+    max_virtual_memory: Option<u64>,
+    /// Bytes.
+    max_stack_size: Option<u64>,
+    /// Seconds.
+    max_cpu_time: Option<u64>,
+    max_processes: Option<u64>,
+    max_processes_hard: Option<u64>,
+    /// Descriptors open right now, if they could be counted.
+    open_files: Option<u32>,
+    /// Whether `open_files` has reached `OPEN_FILES_WARNING_PERCENT` of `max_open_files`.
+    open_files_near_limit: bool,
+}
+
+/// The open-file, process, stack and address-space limits of a process, from
+/// `get_process_limits`. Unlimited, or unknown, limits are `u64::MAX`, except
+/// `max_virtual_mem_kb`, which is None instead.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ProcessLimits {
+    max_open_files_soft: u64,
+    max_open_files_hard: u64,
+    /// 0 when the descriptors can't be counted.
+    current_open_files: u64,
+    max_processes_soft: u64,
+    max_processes_hard: u64,
+    max_stack_size_kb: u64,
+    max_virtual_mem_kb: Option<u64>,
+    /// Whether `current_open_files` has reached `OPEN_FILES_WARNING_PERCENT` of
+    /// `max_open_files_soft`.
+    open_files_near_limit: bool,
+}
+
+impl From<ResourceLimits> for ProcessLimits {
+    fn from(limits: ResourceLimits) -> Self {
+        let count = |limit: Option<u64>| limit.unwrap_or(u64::MAX);
+        let kb = |bytes: Option<u64>| bytes.map(|bytes| bytes / 1024);
+        ProcessLimits {
+            max_open_files_soft: count(limits.max_open_files),
+            max_open_files_hard: count(limits.max_open_files_hard),
+            current_open_files: limits.open_files.map_or(0, u64::from),
+            max_processes_soft: count(limits.max_processes),
+            max_processes_hard: count(limits.max_processes_hard),
+            max_stack_size_kb: count(kb(limits.max_stack_size)),
+            max_virtual_mem_kb: kb(limits.max_virtual_memory),
+            open_files_near_limit: limits.open_files_near_limit,
+        }
+    }
+}
+
+/// Processes that no kill command may signal, checked before every kill and
+/// signal aimed at a single PID. Names match the executable name exactly
+/// (`"systemd"`, `"csrss.exe"`). Users match the process owner, and on Windows
+/// also the part after the domain (`"alice"` for `DESKTOP-1\alice`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KillPolicy {
+    protected_names: Vec<String>,
+    protected_users: Vec<String>,
+}
+
+impl Default for KillPolicy {
+    /// `state::DEFAULT_PROTECTED_NAMES`, with no protected users.
+    fn default() -> Self {
+        KillPolicy {
+            protected_names: state::DEFAULT_PROTECTED_NAMES.iter().map(|name| name.to_string()).collect(),
+            protected_users: Vec::new(),
+        }
+    }
+}
+
+impl KillPolicy {
+    fn validate(&self) -> Result<(), String> {
+        if self.protected_names.iter().chain(&self.protected_users).any(|entry| entry.trim().is_empty()) {
+            return Err("Protected names and users must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Why the policy protects a process with this name and owner, if it does.
+    fn violation(&self, name: Option<&str>, user: Option<&str>) -> Option<String> {
+        if let Some(name) = name.filter(|name| self.protected_names.iter().any(|n| n == name)) {
+            return Some(format!("{} is a protected process", name));
+        }
+        let user = user?;
+        let short_user = user.rsplit('\\').next().unwrap_or(user);
+        self.protected_users
+            .iter()
+            .any(|u| u == user || u == short_user)
+            .then(|| format!("processes owned by {} are protected", user))
+    }
+}
+
+/// Orderings `list_ports` can return, instead of the default sort by name.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    Name,
+    /// Total CPU across the group's PIDs, highest first.
+    Cpu,
+    /// Total resident memory (`rss_kb`) across the group's PIDs, highest first.
+    Memory,
+    /// Lowest PID first.
+    Pid,
+    /// Lowest port number first.
+    Port,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ProcessDetails {
+    command: String,
+    user: String,
+    uid: Option<u32>,
+    cpu: Option<f32>,
+    cpu_time: String,
+    cpu_time_secs: u64,
+    mem: Option<f32>,
+    vsz_kb: u64,
+    rss_kb: u64,
+    start_time: String,
+    uptime_seconds: u64,
+    process_state: String,
+    ppid: u32,
+    pgid: u32,
+    sid: u32,
+    nice: i8,
+    threads: u32,
+    cwd: String,
+    fd_count: u32,
+    exe_path: String,
+    arch: Option<String>,
+    oom_score: Option<i32>,
+    oom_score_adj: Option<i32>,
+    container_id: Option<String>,
+    systemd_unit: Option<String>,
+    cgroup_path: Option<String>,
+    net_rx_bytes: u64,
+    net_tx_bytes: u64,
+    ps_extra: HashMap<String, String>,
+}
+
+#[cfg(not(target_os = "windows"))]
+/// Runs `lsof` with the given arguments and returns its stdout.
+///
+/// `lsof` exits non-zero when nothing matches the selection, so callers that
+/// expect the selection may legitimately be empty can pass `allow_empty`.
+fn run_lsof(args: &[&str], allow_empty: bool) -> Result<String, ListPortsError> {
+    let timeout_ms = tools::lsof_timeout_ms();
+    let output = tools::output_within(tools::lsof().args(args), timeout_ms)
+        .map_err(|e| ListPortsError::from_spawn("lsof", timeout_ms, &e))?;
 
     if !output.status.success() {
-        #[cfg(debug_assertions)]
-        println!("[DEBUG] lsof command failed with status: {}", output.status);
-        return Err("lsof command failed".to_string());
+        if allow_empty && output.stdout.is_empty() && output.stderr.is_empty() {
+            return Ok(String::new());
+        }
+        tracing::warn!("lsof command failed with status: {}", output.status);
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(ListPortsError::from_failure("lsof", stderr));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut process_map: HashMap<u32, (String, Vec<String>, Vec<String>, String, String, String, String)> = HashMap::new();
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A single socket row parsed from `lsof -i` output.
+struct SocketEntry {
+    process_name: String,
+    pid: u32,
+    protocol: String,
+    port: u16,
+    bind_address: String,
+    state: String,
+}
+
+/// Splits a local socket address such as `*:8080`, `127.0.0.1:8080` or `[::1]:443`
+/// into its host (brackets removed) and port. Returns `None` for non-numeric ports like `*`.
+fn split_address(address: &str) -> Option<(String, u16)> {
+    let (host, port) = address.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    Some((host.to_string(), port))
+}
+
+/// Runs `lsof` over internet sockets with `args` and parses the rows, asking
+/// for field output (`-F`) so names with spaces in them can't shift columns.
+/// If this `lsof` rejects the field selection, its columnar output is parsed instead.
+#[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+fn lsof_sockets(args: &[&str], allow_empty: bool) -> Result<Vec<SocketEntry>, ListPortsError> {
+    let field_args = [args, &["-F", "pcftPnT"]].concat();
+    match run_lsof(&field_args, allow_empty) {
+        Ok(stdout) => Ok(parse_lsof_socket_fields(&stdout)),
+        Err(ListPortsError::CommandFailed { stderr, .. }) => {
+            tracing::warn!("lsof field output failed, retrying with columns: {}", stderr);
+            Ok(parse_lsof_sockets(&run_lsof(args, allow_empty)?))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// One descriptor of `lsof -F pcftPnT` output, filled in as its lines are read.
+/// On FreeBSD only `get_network_connections` reads it, which has no use for the process.
+#[cfg(not(target_os = "windows"))]
+#[cfg_attr(target_os = "freebsd", allow(dead_code))]
+struct LsofDescriptor<'a> {
+    pid: u32,
+    process_name: &'a str,
+    /// `IPv4` or `IPv6`.
+    family: &'a str,
+    protocol: &'a str,
+    name: &'a str,
+    state: &'a str,
+}
+
+#[cfg(not(target_os = "windows"))]
+impl LsofDescriptor<'_> {
+    /// `"TCP"`, `"TCP6"`, `"UDP"` or `"UDP6"`, as in `SocketEntry.protocol`.
+    fn protocol(&self) -> String {
+        match self.family {
+            "IPv6" => format!("{}6", self.protocol),
+            _ => self.protocol.to_string(),
+        }
+    }
+}
+
+/// Splits `lsof -F pcftPnT` field output into descriptors: a `p`/`c` (pid,
+/// command) set per process, then per descriptor an `f` line followed by its
+/// address family (`t`), protocol (`P`), address (`n`) and, for TCP, its state
+/// as `TST=LISTEN` among the other `T` lines.
+#[cfg(not(target_os = "windows"))]
+fn parse_lsof_descriptors(stdout: &str) -> Vec<LsofDescriptor<'_>> {
+    let mut descriptors: Vec<LsofDescriptor> = Vec::new();
+    let (mut pid, mut process_name) = (0, "");
+
+    for line in stdout.lines() {
+        let Some(field) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[field.len_utf8()..];
+        match (field, descriptors.last_mut()) {
+            ('p', _) => pid = value.parse().unwrap_or(0),
+            ('c', _) => process_name = value,
+            ('f', _) => descriptors.push(LsofDescriptor {
+                pid,
+                process_name,
+                family: "",
+                protocol: "",
+                name: "",
+                state: "",
+            }),
+            ('t', Some(descriptor)) => descriptor.family = value,
+            ('P', Some(descriptor)) => descriptor.protocol = value,
+            ('n', Some(descriptor)) => descriptor.name = value,
+            ('T', Some(descriptor)) => {
+                if let Some(state) = value.strip_prefix("ST=") {
+                    descriptor.state = state;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    descriptors
+}
+
+/// Parses `lsof -F pcftPnT` field output (see `parse_lsof_descriptors`) into
+/// rows as `parse_lsof_sockets` gives them.
+#[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+fn parse_lsof_socket_fields(stdout: &str) -> Vec<SocketEntry> {
+    tracing::debug!("Parsing lsof field output, {} lines", stdout.lines().count());
+
+    parse_lsof_descriptors(stdout)
+        .into_iter()
+        .filter_map(|descriptor| {
+            // Connected sockets report "local->remote"; only the local side is relevant
+            let address = descriptor.name.split("->").next().unwrap_or(descriptor.name);
+            let (bind_address, port) = split_address(address)?;
+            Some(SocketEntry {
+                process_name: descriptor.process_name.to_string(),
+                pid: descriptor.pid,
+                protocol: descriptor.protocol(),
+                port,
+                bind_address,
+                state: descriptor.state.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses columnar `lsof -i` output, for an `lsof` without field output.
+#[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+fn parse_lsof_sockets(stdout: &str) -> Vec<SocketEntry> {
+    tracing::debug!("Parsing lsof output, {} lines", stdout.lines().count());
 
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] Parsing lsof output, {} lines", stdout.lines().count());
+    let mut sockets = Vec::new();
 
     for line in stdout.lines().skip(1) {
         // Skip header
@@ -54,159 +935,3625 @@ fn list_ports() -> Result<Vec<PortInfo>, String> {
 
         let process_name = parts[0].to_string();
         let pid = parts[1].parse::<u32>().unwrap_or(0);
-        let protocol = parts[7].to_string();
-        let address = parts[8];
-
-        // Extract port from address (format: *:PORT or IP:PORT)
-        if let Some(port_str) = address.split(':').last() {
-            // Filter out non-numeric ports
-            if port_str.chars().all(|c| c.is_numeric()) {
-                let entry = process_map.entry(pid).or_insert((
-                    process_name.clone(),
-                    Vec::new(),
-                    Vec::new(),
-                    String::new(),
-                    String::new(),
-                    String::new(),
-                    String::new(),
-                ));
-                if !entry.1.contains(&port_str.to_string()) {
-                    entry.1.push(port_str.to_string());
-                }
-                if !entry.2.contains(&protocol) {
-                    entry.2.push(protocol);
-                }
-            }
+        let protocol = match parts[4] {
+            "IPv6" => format!("{}6", parts[7]),
+            _ => parts[7].to_string(),
+        };
+        // Connected sockets report "local->remote"; only the local side is relevant
+        let address = parts[8].split("->").next().unwrap_or(parts[8]);
+        // TCP rows end with the connection state in parentheses, e.g. "(LISTEN)"
+        let state = parts
+            .get(9)
+            .map(|s| s.trim_matches(|c| c == '(' || c == ')').to_string())
+            .unwrap_or_default();
+
+        if let Some((bind_address, port)) = split_address(address) {
+            sockets.push(SocketEntry {
+                process_name,
+                pid,
+                protocol,
+                port,
+                bind_address,
+                state,
+            });
         }
     }
 
-    // Get additional process info for each process
-    for (pid, entry) in process_map.iter_mut() {
-        // Get command, user, cpu, and memory - use column-based parsing
-        if let Ok(ps_output) = Command::new("ps")
-            .args(["-p", &pid.to_string(), "-o", "user=,%cpu=,%mem=,command="])
-            .output()
-        {
-            let ps_line = String::from_utf8_lossy(&ps_output.stdout).trim().to_string();
+    sockets
+}
 
-            // Split into at most 4 parts using whitespace
-            let mut parts = Vec::new();
-            let mut current = String::new();
-            let mut word_count = 0;
+/// Parses `lsof -U -F pcLn` field output: a `p`/`c`/`L` (pid, command, login) set
+/// per process, followed by one `n` line per socket. On Linux the name ends with
+/// ` type=STREAM` and so on, and is just `type=STREAM` for unnamed sockets.
+#[cfg(not(target_os = "windows"))]
+fn parse_lsof_unix_sockets(stdout: &str) -> Vec<UnixSocketInfo> {
+    let mut sockets = Vec::new();
+    let (mut pid, mut process_name, mut user) = (0, "", "");
 
-            for word in ps_line.split_whitespace() {
-                if word_count < 3 {
-                    parts.push(word.to_string());
-                    word_count += 1;
+    for line in stdout.lines() {
+        let Some(field) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[field.len_utf8()..];
+        match field {
+            'p' => pid = value.parse().unwrap_or(0),
+            'c' => process_name = value,
+            'L' => user = value,
+            'n' => {
+                let (path, socket_type) = match value.split_once("type=") {
+                    Some((path, rest)) => (path.trim(), rest.split_whitespace().next().unwrap_or_default()),
+                    None => (value.trim(), ""),
+                };
+                // macOS names unnamed sockets by kernel address, e.g. "->0xabc123"
+                let socket_path = if path.starts_with('/') || path.starts_with('@') {
+                    path.to_string()
                 } else {
-                    if !current.is_empty() {
-                        current.push(' ');
-                    }
-                    current.push_str(word);
-                }
+                    "(anonymous)".to_string()
+                };
+                sockets.push(UnixSocketInfo {
+                    process_name: process_name.to_string(),
+                    pid,
+                    user: user.to_string(),
+                    socket_path,
+                    socket_type: socket_type.to_string(),
+                });
             }
-            if !current.is_empty() {
-                parts.push(current);
+            _ => {}
+        }
+    }
+
+    sockets
+}
+
+/// Parses `lsof -F ftDsin` field output, in which each descriptor starts with
+/// an `f` line followed by whichever of its other fields are known.
+#[cfg(not(target_os = "windows"))]
+fn parse_lsof_open_files(stdout: &str) -> Vec<OpenFileInfo> {
+    let mut files: Vec<OpenFileInfo> = Vec::new();
+
+    for line in stdout.lines() {
+        let Some(field) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[field.len_utf8()..];
+        if field == 'f' {
+            files.push(OpenFileInfo {
+                fd: value.to_string(),
+                type_: String::new(),
+                device: String::new(),
+                size: 0,
+                node: String::new(),
+                name: String::new(),
+            });
+            continue;
+        }
+        // Process-level fields (p) come before the first descriptor
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        match field {
+            't' => file.type_ = value.to_string(),
+            'D' => file.device = value.to_string(),
+            's' => file.size = value.parse().unwrap_or(0),
+            'i' => file.node = value.to_string(),
+            'n' => file.name = value.to_string(),
+            _ => {}
+        }
+    }
+
+    files
+}
+
+/// Parses `lsof -F pcftPnT` field output into connections, keeping both ends
+/// of the `n` (name) field.
+#[cfg(not(target_os = "windows"))]
+fn parse_lsof_connections(stdout: &str) -> Vec<ConnectionInfo> {
+    parse_lsof_descriptors(stdout)
+        .into_iter()
+        .filter(|descriptor| !descriptor.name.is_empty())
+        .map(|descriptor| {
+            let (local_addr, remote_addr) = descriptor.name.split_once("->").unwrap_or((descriptor.name, "*"));
+            ConnectionInfo {
+                local_addr: local_addr.to_string(),
+                remote_addr: remote_addr.to_string(),
+                protocol: descriptor.protocol(),
+                state: descriptor.state.to_string(),
             }
+        })
+        .collect()
+}
+
+/// Single-word `ps` columns, requested ahead of the multi-word `lstart` and `command`.
+/// `ni` is the nice alias both Linux and macOS accept. `sid` and `nlwp` (thread
+/// count) are Linux-only; asking BSD-derived `ps` for them fails the whole call.
+/// Uptime comes from `etime` rather than from `lstart`, which is local time with
+/// no UTC offset to subtract from.
+#[cfg(target_os = "linux")]
+const PS_WORD_COLUMNS: &[&str] = &[
+    "user", "uid", "%cpu", "cputime", "%mem", "vsz", "rss", "stat", "ppid", "pgid", "sid", "ni", "nlwp", "etime",
+];
+#[cfg(all(not(target_os = "linux"), not(target_os = "windows")))]
+const PS_WORD_COLUMNS: &[&str] = &[
+    "user", "uid", "%cpu", "cputime", "%mem", "vsz", "rss", "stat", "ppid", "pgid", "ni", "etime",
+];
+
+/// Further single-word columns `set_ps_format` accepts, which both Linux
+/// and BSD-derived `ps` know. Their values are passed through in `ps_extra`.
+#[cfg(not(target_os = "windows"))]
+const EXTRA_PS_COLUMNS: &[&str] = &["gid", "rgid", "ruser", "pri", "tty", "wchan"];
 
-            if parts.len() >= 4 {
-                entry.4 = parts[0].clone(); // user
-                entry.5 = parts[1].clone(); // %cpu
-                entry.6 = parts[2].clone(); // %mem
-                entry.3 = parts[3].clone(); // full command (everything after first 3 fields)
+/// Parses a `ps` elapsed time (`[[dd-]hh:]mm:ss`, e.g. `"3-01:05:12"`) into seconds.
+#[cfg(not(target_os = "windows"))]
+fn parse_elapsed(etime: &str) -> Option<u64> {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+
+    let mut seconds = 0;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86_400 + seconds)
+}
+
+/// Parses a `ps` CPU time into whole seconds. Linux prints `[dd-]hh:mm:ss`,
+/// the same shape as `etime`; macOS prints `mm:ss.ss`, whose fraction is dropped.
+/// Processes that have never been scheduled (e.g. kernel threads) report `-`.
+#[cfg(not(target_os = "windows"))]
+fn parse_cpu_time(cpu_time: &str) -> Option<u64> {
+    let whole = cpu_time.split('.').next()?;
+    parse_elapsed(whole)
+}
+
+/// Counts threads on macOS, where `ps -M` prints one row per thread after a header.
+#[cfg(target_os = "macos")]
+fn thread_count(pid: u32) -> Result<u32, ListPortsError> {
+    let timeout_ms = tools::ps_timeout_ms();
+    let output = tools::output_within(tools::ps().args(["-M", "-p", &pid.to_string()]), timeout_ms)
+        .map_err(|e| ListPortsError::from_spawn("ps", timeout_ms, &e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).lines().skip(1).count() as u32)
+}
+
+/// Looks up `ps` details (user, cpu, memory, state, parent, nice, threads, start time, command)
+/// for each PID, plus the `inspect` lookups cheap enough for every listing (see `fill_slow_details`).
+///
+/// With the `parallel-ps` feature (on by default) the PIDs are split across up
+/// to `available_parallelism()` threads, so a listing isn't bound by dozens of
+/// serial `ps` invocations.
+#[cfg(not(target_os = "windows"))]
+fn collect_process_details(pids: impl IntoIterator<Item = u32>) -> HashMap<u32, ProcessDetails> {
+    let columns = tools::ps_columns();
+    let format: String = columns
+        .iter()
+        .map(|column| format!("{}=,", column))
+        .chain(["lstart=,command=".to_string()])
+        .collect();
+
+    let mut pids: Vec<u32> = pids.into_iter().collect();
+    pids.sort_unstable();
+    pids.dedup();
+
+    #[cfg(feature = "parallel-ps")]
+    let details_map = thread::scope(|scope| {
+        let (columns, format) = (columns.as_slice(), format.as_str());
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let lookup = move |chunk: &[u32]| -> Vec<(u32, ProcessDetails)> {
+            chunk.iter().map(|&pid| (pid, process_details(pid, columns, format))).collect()
+        };
+        let handles: Vec<_> = pids
+            .chunks(pids.len().div_ceil(workers).max(1))
+            .map(|chunk| (chunk, scope.spawn(move || lookup(chunk))))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|(chunk, handle)| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| chunk.iter().map(|&pid| (pid, ProcessDetails::default())).collect())
+            })
+            .collect()
+    });
+
+    #[cfg(not(feature = "parallel-ps"))]
+    let details_map = pids
+        .into_iter()
+        .map(|pid| (pid, process_details(pid, &columns, &format)))
+        .collect();
+
+    details_map
+}
+
+/// Runs `ps -p <pid> -o <format>` and the `inspect` lookups for a single PID.
+/// `columns` are the word columns at the start of `format`.
+#[cfg(not(target_os = "windows"))]
+fn process_details(pid: u32, columns: &[&str], format: &str) -> ProcessDetails {
+    let mut details = ProcessDetails::default();
+
+    let mut ps = tools::ps();
+    ps.args(["-p", &pid.to_string(), "-o", format]);
+    let ps_output = tools::output_within(&mut ps, tools::ps_timeout_ms());
+    if let Err(e) = &ps_output {
+        tracing::warn!("ps for PID {} failed: {}", pid, e);
+    }
+
+    // Use column-based parsing: the word columns are one word each and lstart
+    // is always five ("Thu Jan  1 00:00:00 2025"); the command is everything after that
+    if let Ok(ps_output) = ps_output {
+        let ps_line = String::from_utf8_lossy(&ps_output.stdout).trim().to_string();
+        let words: Vec<&str> = ps_line.split_whitespace().collect();
+        let lstart_end = columns.len() + 5;
+
+        if words.len() > lstart_end {
+            for (column, word) in columns.iter().zip(&words) {
+                match *column {
+                    "user" => details.user = word.to_string(),
+                    "uid" => details.uid = word.parse().ok(),
+                    "%cpu" => details.cpu = word.parse().ok(),
+                    "cputime" => {
+                        details.cpu_time = word.to_string();
+                        details.cpu_time_secs = parse_cpu_time(word).unwrap_or(0);
+                    }
+                    "%mem" => details.mem = word.parse().ok(),
+                    // Both are reported in KiB on Linux and macOS
+                    "vsz" => details.vsz_kb = word.parse().unwrap_or(0),
+                    "rss" => details.rss_kb = word.parse().unwrap_or(0),
+                    // stat carries modifier flags after the state letter (e.g. "Ss+")
+                    "stat" => details.process_state = word.chars().take(1).collect(),
+                    "ppid" => details.ppid = word.parse().unwrap_or(0),
+                    "pgid" => details.pgid = word.parse().unwrap_or(0),
+                    "sid" => details.sid = word.parse().unwrap_or(0),
+                    // Real-time processes report "-" instead of a nice value
+                    "ni" => details.nice = word.parse().unwrap_or(0),
+                    "nlwp" => details.threads = word.parse().unwrap_or(0),
+                    "etime" => details.uptime_seconds = parse_elapsed(word).unwrap_or(0),
+                    // One of EXTRA_PS_COLUMNS
+                    column => {
+                        details.ps_extra.insert(column.to_string(), word.to_string());
+                    }
+                }
             }
+            details.start_time = words[columns.len()..lstart_end].join(" ");
+            details.command = words[lstart_end..].join(" ");
+        }
+    }
+
+    // Minimal images may not ship ps at all; /proc has the same figures. ps
+    // always prints a command when it succeeds, even "[kthreadd]" for kernel threads
+    #[cfg(target_os = "linux")]
+    if details.command.is_empty() {
+        procfs::fill_details(pid, &mut details);
+    }
+
+    // Best-effort: permission errors leave the field empty instead of failing the listing
+    details.oom_score = inspect::oom_score(pid).ok();
+    details.oom_score_adj = inspect::oom_score_adj(pid).ok();
+    details.container_id = inspect::container_id(pid);
+    details.systemd_unit = inspect::systemd_unit(pid);
+    details.cgroup_path = inspect::cgroup_path(pid);
+    #[cfg(target_os = "linux")]
+    if let Some((rx, tx)) = inspect::net_io(pid) {
+        (details.net_rx_bytes, details.net_tx_bytes) = (rx, tx);
+    }
+
+    details
+}
+
+/// Fills the fields that cost further `lsof` or `ps` runs per PID, which
+/// `list_ports` leaves empty: the working directory, the descriptor count, the
+/// executable and its architecture, and on macOS the thread count.
+#[cfg(not(target_os = "windows"))]
+fn fill_slow_details(pid: u32, details: &mut ProcessDetails) {
+    #[cfg(target_os = "macos")]
+    {
+        details.threads = thread_count(pid).unwrap_or(0);
+    }
+
+    details.cwd = inspect::cwd(pid).unwrap_or_default();
+    details.fd_count = inspect::fd_count(pid).unwrap_or_default();
+    details.exe_path = inspect::exe_path(pid).unwrap_or_default();
+    if !details.exe_path.is_empty() {
+        details.arch = inspect::arch(pid, &details.exe_path);
+    }
+}
+
+/// (pid, protocol, state, bind_address): the sockets merged into one `PidInfo`.
+type SocketKey = (u32, String, String, String);
+
+/// Combines parsed sockets with their process details and groups them into `PortInfo`s.
+///
+/// Sockets are merged per (pid, protocol, state, bind address) so a PID with both
+/// a TCP and a UDP socket on the same port number is reported once per protocol,
+/// and ports bound to loopback stay apart from ports bound to all interfaces.
+/// Groups are keyed by (process_name, command), plus the socket state when
+/// `group_by_state` is set.
+fn build_port_info(
+    sockets: Vec<SocketEntry>,
+    details_map: &HashMap<u32, ProcessDetails>,
+    group_by_state: bool,
+) -> Vec<PortInfo> {
+    let mut process_map: HashMap<SocketKey, (String, Vec<u16>)> = HashMap::new();
+    let mut pid_protocols: HashMap<u32, Vec<String>> = HashMap::new();
+
+    for socket in sockets {
+        let family = if socket.protocol.ends_with('6') {
+            socket.protocol.clone()
+        } else {
+            format!("{}4", socket.protocol)
+        };
+        let families = pid_protocols.entry(socket.pid).or_default();
+        if !families.contains(&family) {
+            families.push(family);
         }
+
+        let entry = process_map
+            .entry((socket.pid, socket.protocol, socket.state, socket.bind_address))
+            .or_insert((socket.process_name, Vec::new()));
+        if !entry.1.contains(&socket.port) {
+            entry.1.push(socket.port);
+        }
+    }
+
+    for families in pid_protocols.values_mut() {
+        families.sort_unstable();
     }
 
-    // Group by process name and command
-    let mut process_groups: HashMap<(String, String), Vec<(u32, Vec<String>, String, String, String)>> = HashMap::new();
+    // Group by process name and command (and state, if requested)
+    let mut process_groups: HashMap<(String, String, String), Vec<PidInfo>> = HashMap::new();
 
-    for (pid, (process_name, mut port_list, _protocol_list, command, user, cpu, mem)) in process_map {
-        port_list.sort_by_key(|p| p.parse::<u32>().unwrap_or(0));
+    for ((pid, protocol, state, bind_address), (process_name, mut port_list)) in process_map {
+        port_list.sort_unstable();
 
-        let key = (process_name.clone(), command.clone());
-        process_groups.entry(key).or_insert_with(Vec::new).push((pid, port_list, user, cpu, mem));
+        let details = details_map.get(&pid).cloned().unwrap_or_default();
+        let group_state = if group_by_state { state.clone() } else { String::new() };
+        let key = (process_name, details.command.clone(), group_state);
+        let mut pid_info = PidInfo::from_details(pid, protocol, state, port_list, bind_address, details);
+        pid_info.protocols = pid_protocols.get(&pid).cloned().unwrap_or_default();
+        process_groups.entry(key).or_default().push(pid_info);
     }
 
     // Convert to PortInfo structs
     let mut ports: Vec<PortInfo> = process_groups
         .into_iter()
-        .map(|((process_name, command), mut pid_list)| {
-            // Sort PIDs
-            pid_list.sort_by_key(|(pid, _, _, _, _)| *pid);
-
-            let pids = pid_list
-                .into_iter()
-                .map(|(pid, port_list, user, cpu, mem)| PidInfo {
-                    pid,
-                    ports: port_list.join(", "),
-                    user,
-                    cpu,
-                    mem,
-                })
-                .collect();
+        .map(|((process_name, command, _), mut pids)| {
+            // Sort PIDs, keeping each PID's protocols and addresses in a stable order
+            pids.sort_by(|a, b| {
+                (a.pid, &a.protocol, &a.state, &a.bind_address).cmp(&(b.pid, &b.protocol, &b.state, &b.bind_address))
+            });
 
             PortInfo {
                 process_name,
                 command,
                 pids,
+                connection_count: 0,
             }
         })
         .collect();
 
     // Sort by process name (case-insensitive)
-    ports.sort_by(|a, b| a.process_name.to_lowercase().cmp(&b.process_name.to_lowercase()));
+    ports.sort_by_key(|p| p.process_name.to_lowercase());
 
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] Returning {} unique process groups", ports.len());
+    tracing::debug!("Returning {} unique process groups", ports.len());
 
-    Ok(ports)
+    ports
 }
 
-#[tauri::command]
-fn kill_process(pid: u32) -> Result<String, String> {
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] Attempting to kill process with PID: {}", pid);
+/// Collects every listening TCP socket and bound UDP socket, grouped and sorted by name.
+fn collect_port_info() -> Result<Vec<PortInfo>, ListPortsError> {
+    collect_listening_ports(None, |_| true)
+}
 
-    let output = Command::new("kill")
-        .args(["-9", &pid.to_string()])
-        .output()
-        .map_err(|e| {
-            #[cfg(debug_assertions)]
-            println!("[DEBUG] Failed to execute kill command: {}", e);
-            format!("Failed to kill process: {}", e)
-        })?;
+/// Collects listening sockets, optionally restricted to processes owned by `user`,
+/// keeping only the sockets `keep` accepts.
+fn collect_listening_ports(
+    user: Option<&str>,
+    keep: impl Fn(&SocketEntry) -> bool,
+) -> Result<Vec<PortInfo>, ListPortsError> {
+    #[cfg(target_os = "windows")]
+    let (sockets, details_map) = {
+        let (mut sockets, details_map) = windows::collect_sockets(true)?;
+        sockets.retain(&keep);
+        if let Some(user) = user {
+            // tasklist reports users as DOMAIN\name
+            sockets.retain(|s| {
+                details_map
+                    .get(&s.pid)
+                    .is_some_and(|d| d.user == user || d.user.rsplit('\\').next() == Some(user))
+            });
+        }
+        (sockets, details_map)
+    };
 
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] Kill command exit status: {}", output.status);
+    #[cfg(target_os = "freebsd")]
+    let (sockets, details_map) = {
+        let mut sockets = freebsd::listening_sockets(user)?;
+        sockets.retain(&keep);
+        let details_map = collect_process_details(sockets.iter().map(|s| s.pid));
+        (sockets, details_map)
+    };
 
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] Kill command stdout: {}", String::from_utf8_lossy(&output.stdout));
+    #[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+    let (sockets, details_map) = {
+        // -a ANDs the selections; otherwise lsof lists the user's files OR network files
+        let user_args: Vec<&str> = match user {
+            Some(user) => vec!["-a", "-u", user],
+            None => Vec::new(),
+        };
+        let tcp_args = [user_args.as_slice(), &["-iTCP", "-P", "-n", "-sTCP:LISTEN"]].concat();
+        let udp_args = [user_args.as_slice(), &["-iUDP", "-P", "-n"]].concat();
 
-    #[cfg(debug_assertions)]
-    println!("[DEBUG] Kill command stderr: {}", String::from_utf8_lossy(&output.stderr));
+        // Use -sTCP:LISTEN to only show listening TCP ports (servers), not outbound connections.
+        // UDP has no listen state, so every bound UDP socket is collected in a second pass.
+        // A single user may legitimately have no listeners at all.
+        let mut sockets = lsof_sockets(&tcp_args, user.is_some())?;
+        sockets.extend(lsof_sockets(&udp_args, true)?);
+        sockets.retain(&keep);
+        let details_map = collect_process_details(sockets.iter().map(|s| s.pid));
+        (sockets, details_map)
+    };
 
-    if output.status.success() {
-        #[cfg(debug_assertions)]
-        println!("[DEBUG] Process {} killed successfully", pid);
-        Ok(format!("Process {} killed successfully", pid))
-    } else {
-        let err_msg = format!(
-            "Failed to kill process {}: {}",
-            pid,
-            String::from_utf8_lossy(&output.stderr)
-        );
-        #[cfg(debug_assertions)]
-        println!("[DEBUG] {}", err_msg);
-        Err(err_msg)
-    }
-}
+    #[cfg(target_os = "windows")]
+    let ports = build_port_info(sockets, &details_map, false);
+
+    #[cfg(not(target_os = "windows"))]
+    let ports = {
+        let mut ports = build_port_info(sockets, &details_map, false);
+        fill_connection_counts(&mut ports);
+        ports
+    };
+
+    Ok(ports)
+}
+
+/// Sets each group's `connection_count` from the connected sockets of every PID
+/// in the listing. If they can't be listed the counts stay at 0.
+#[cfg(not(target_os = "windows"))]
+fn fill_connection_counts(ports: &mut [PortInfo]) {
+    let pids: HashSet<u32> = ports.iter().flat_map(|p| p.pids.iter().map(|pid_info| pid_info.pid)).collect();
+    if pids.is_empty() {
+        return;
+    }
+
+    #[cfg(target_os = "freebsd")]
+    let per_pid = freebsd::connections_per_pid();
+    #[cfg(not(target_os = "freebsd"))]
+    let per_pid = lsof_connections_per_pid(&pids);
+    let per_pid = match per_pid {
+        Ok(per_pid) => per_pid,
+        Err(e) => {
+            tracing::warn!("Skipping connection counts: {}", e);
+            return;
+        }
+    };
+
+    for port_info in ports {
+        // A PID listed once per protocol or address still counts once
+        let group_pids: HashSet<u32> = port_info.pids.iter().map(|pid_info| pid_info.pid).collect();
+        port_info.connection_count = group_pids.iter().filter_map(|pid| per_pid.get(pid)).sum();
+    }
+}
+
+/// Counts the connected sockets of `pids` in one `lsof -a -i -p <pids> -F pn`
+/// pass: the names with a remote end (`local->remote`).
+#[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+fn lsof_connections_per_pid(pids: &HashSet<u32>) -> Result<HashMap<u32, u32>, ListPortsError> {
+    let pid_list = pids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+
+    // -a ANDs the selections; otherwise lsof lists network files OR the PIDs' files
+    let stdout = run_lsof(&["-a", "-i", "-P", "-n", "-p", &pid_list, "-F", "pn"], true)?;
+
+    let mut per_pid: HashMap<u32, u32> = HashMap::new();
+    let mut pid = 0;
+    for line in stdout.lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse().unwrap_or(0);
+        } else if line.starts_with('n') && line.contains("->") {
+            *per_pid.entry(pid).or_default() += 1;
+        }
+    }
+    Ok(per_pid)
+}
+
+/// Reorders `ports` by `sort_by`. The sort is stable, so ties keep their name order.
+fn sort_ports(ports: &mut [PortInfo], sort_by: SortField) {
+    // Unavailable readings count as 0
+    let total_cpu = |p: &PortInfo| -> f32 { p.pids.iter().filter_map(|pid| pid.cpu).sum() };
+    let total_rss = |p: &PortInfo| -> u64 { p.pids.iter().map(|pid| pid.rss_kb).sum() };
+
+    match sort_by {
+        SortField::Name => ports.sort_by_key(|p| p.process_name.to_lowercase()),
+        // Highest consumers first
+        SortField::Cpu => ports.sort_by(|a, b| total_cpu(b).total_cmp(&total_cpu(a))),
+        SortField::Memory => ports.sort_by_key(|p| std::cmp::Reverse(total_rss(p))),
+        SortField::Pid => ports.sort_by_key(|p| p.pids.iter().map(|pid| pid.pid).min().unwrap_or(u32::MAX)),
+        SortField::Port => ports.sort_by_key(|p| {
+            p.pids
+                .iter()
+                .flat_map(|pid| pid.ports.iter().copied())
+                .min()
+                .unwrap_or(u16::MAX)
+        }),
+    }
+}
+
+/// Lists listening ports, sorted by process name unless `sort_by` says otherwise,
+/// with groups containing a pinned PID moved to the front in the same order.
+/// Results younger than the `AppState` cache TTL are served without running `lsof`.
+///
+/// Collecting a listing blocks on `lsof` and `ps` for up to seconds, so it runs
+/// on the blocking thread pool instead of the IPC thread, which stays free to
+/// handle other commands meanwhile.
+#[tauri::command]
+async fn list_ports(app: AppHandle, sort_by: Option<SortField>) -> Result<PortListing, ListPortsError> {
+    tracing::debug!("list_ports command called with sort {:?}", sort_by);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        sorted_ports(&app.state::<AppState>(), &**app.state::<DynProvider>(), sort_by)
+    })
+    .await
+    .map_err(|e| ListPortsError::SpawnError(format!("Listing task failed: {}", e)))?
+}
+
+/// The `list_ports` listing, cached and sorted, pinned groups first.
+fn sorted_ports(
+    state: &AppState,
+    provider: &dyn ProcessInfoProvider,
+    sort_by: Option<SortField>,
+) -> Result<PortListing, ListPortsError> {
+    let (request_id, mut ports) = state.tracked_ports(|| provider.get_listening_ports())?;
+    if let Some(sort_by) = sort_by {
+        sort_ports(&mut ports, sort_by);
+    }
+
+    let pinned = state.watchlist().pids();
+    if !pinned.is_empty() {
+        // Stable, so both partitions keep the requested order
+        ports.sort_by_key(|p| !p.pids.iter().any(|pid_info| pinned.contains(&pid_info.pid)));
+    }
+    Ok(PortListing { request_id, ports })
+}
+
+/// Pins `pid` to the top of `list_ports`, across restarts until unpinned. The
+/// PID needn't be listening yet; it is pinned whenever it shows up.
+#[tauri::command]
+fn pin_process(state: State<'_, AppState>, pid: u32) -> Result<(), String> {
+    tracing::debug!("pin_process command called for PID: {}", pid);
+
+    if pid == 0 {
+        return Err("PID 0 cannot be pinned".to_string());
+    }
+    state.watchlist().pin(pid)
+}
+
+#[tauri::command]
+fn unpin_process(state: State<'_, AppState>, pid: u32) -> Result<(), String> {
+    tracing::debug!("unpin_process command called for PID: {}", pid);
+
+    state.watchlist().unpin(pid)
+}
+
+/// Returns the groups of the `list_ports` listing that contain a pinned PID,
+/// trimmed to just the pinned PIDs. Pinned PIDs that aren't running are left out.
+#[tauri::command]
+fn list_pinned(state: State<'_, AppState>, provider: State<'_, DynProvider>) -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("list_pinned command called");
+
+    let pinned = state.watchlist().pids();
+    let mut ports = state.cached_ports(|| provider.get_listening_ports())?;
+    ports.retain_mut(|port_info| {
+        port_info.pids.retain(|pid_info| pinned.contains(&pid_info.pid));
+        !port_info.pids.is_empty()
+    });
+    Ok(ports)
+}
+
+/// Summarizes the `list_ports` listing: how many ports are bound, over which
+/// protocols, by which users, and in which IANA range most of them fall.
+#[tauri::command]
+fn get_port_statistics(state: State<'_, AppState>, provider: State<'_, DynProvider>) -> Result<PortStatistics, String> {
+    tracing::debug!("get_port_statistics command called");
+
+    let ports = state.cached_ports(|| provider.get_listening_ports())?;
+    Ok(PortStatistics::from_ports(&ports))
+}
+
+/// Returns the `list_ports` listing as Prometheus exposition text: CPU, memory
+/// and port-count gauges per PID. The API server serves the same at `/metrics`.
+#[tauri::command]
+fn get_metrics(state: State<'_, AppState>, provider: State<'_, DynProvider>) -> Result<String, String> {
+    tracing::debug!("get_metrics command called");
+
+    let ports = state.cached_ports(|| provider.get_listening_ports())?;
+    Ok(export::prometheus(&ports))
+}
+
+/// Returns the groups of the `list_ports` listing whose process name or command
+/// matches the regex `pattern`, so an incremental search doesn't pull the whole
+/// listing over IPC on every keystroke. Served from the listing cache like `list_ports`.
+#[tauri::command]
+fn search_processes(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pattern: String,
+) -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("search_processes command called with pattern {:?}", pattern);
+
+    let regex = Regex::new(&pattern).map_err(|e| format!("Invalid search pattern: {}", e))?;
+    let mut ports = state.cached_ports(|| provider.get_listening_ports())?;
+    ports.retain(|port_info| regex.is_match(&port_info.process_name) || regex.is_match(&port_info.command));
+    Ok(ports)
+}
+
+/// Returns up to `limit` groups of the `list_ports` result starting at `offset`,
+/// sorted before slicing so consecutive pages follow one ordering. An `offset`
+/// past the end gives an empty page rather than an error.
+#[tauri::command]
+fn list_ports_page(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    offset: usize,
+    limit: usize,
+    sort_by: Option<SortField>,
+) -> Result<PagedResult<PortInfo>, String> {
+    tracing::debug!(
+        "list_ports_page command called with offset {}, limit {}, sort {:?}",
+        offset,
+        limit,
+        sort_by
+    );
+
+    let ports = sorted_ports(&state, &**provider, sort_by)?.ports;
+    let total = ports.len();
+    let items = ports.into_iter().skip(offset).take(limit).collect();
+    Ok(PagedResult {
+        items,
+        total,
+        offset,
+        limit,
+    })
+}
+
+/// Returns the `n` groups using the most of `sort_by`, summed across their PIDs.
+fn top_ports(
+    state: &AppState,
+    provider: &dyn ProcessInfoProvider,
+    n: usize,
+    sort_by: SortField,
+) -> Result<Vec<PortInfo>, String> {
+    if n == 0 {
+        return Err("n must be greater than 0".to_string());
+    }
+
+    let mut ports = state.cached_ports(|| provider.get_listening_ports())?;
+    sort_ports(&mut ports, sort_by);
+    ports.truncate(n);
+    Ok(ports)
+}
+
+#[tauri::command]
+fn top_by_cpu(state: State<'_, AppState>, provider: State<'_, DynProvider>, n: usize) -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("top_by_cpu command called with n = {}", n);
+
+    top_ports(&state, &**provider, n, SortField::Cpu)
+}
+
+#[tauri::command]
+fn top_by_memory(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    n: usize,
+) -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("top_by_memory command called with n = {}", n);
+
+    top_ports(&state, &**provider, n, SortField::Memory)
+}
+
+/// Writes the current `list_ports` result to `path` as JSON, with export metadata.
+#[tauri::command]
+fn export_to_json(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    path: String,
+) -> Result<String, String> {
+    tracing::debug!("export_to_json command called with path {}", path);
+
+    let ports = state.cached_ports(|| provider.get_listening_ports())?;
+    export::write_json(std::path::Path::new(&path), &ports)?;
+    Ok(format!("Exported {} process groups to {}", ports.len(), path))
+}
+
+/// Writes the current `list_ports` result to `path` as CSV, one row per PID.
+#[tauri::command]
+fn export_to_csv(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    path: String,
+) -> Result<String, String> {
+    tracing::debug!("export_to_csv command called with path {}", path);
+
+    let ports = state.cached_ports(|| provider.get_listening_ports())?;
+    export::write_csv(std::path::Path::new(&path), &ports)?;
+    Ok(format!("Exported {} process groups to {}", ports.len(), path))
+}
+
+/// Returns what changed since the previous `list_ports_diff` call. The first call,
+/// and the first after `clear_cache`, reports every group as added.
+#[tauri::command]
+fn list_ports_diff(state: State<'_, AppState>, provider: State<'_, DynProvider>) -> Result<PortsDiff, ListPortsError> {
+    tracing::debug!("list_ports_diff command called");
+
+    let current = state.fresh_ports(|| provider.get_listening_ports())?;
+    let previous = state.replace_snapshot(current.clone());
+    Ok(PortsDiff::between(&previous.unwrap_or_default(), &current))
+}
+
+/// Saves the current `list_ports` listing for a later `compare_snapshots`,
+/// returning its id. Only the latest `MAX_SNAPSHOTS` are kept.
+#[tauri::command]
+fn take_snapshot(state: State<'_, AppState>, provider: State<'_, DynProvider>) -> Result<String, ListPortsError> {
+    tracing::debug!("take_snapshot command called");
+
+    let ports = state.cached_ports(|| provider.get_listening_ports())?;
+    Ok(state.save_snapshot(ports).id)
+}
+
+/// Compares snapshot `id_a` (before) with `id_b` (after).
+#[tauri::command]
+fn compare_snapshots(state: State<'_, AppState>, id_a: String, id_b: String) -> Result<SnapshotDiff, String> {
+    tracing::debug!("compare_snapshots command called with {} and {}", id_a, id_b);
+
+    let saved = |id: &str| state.saved_snapshot(id).ok_or_else(|| format!("No snapshot with id {}", id));
+    Ok(SnapshotDiff::between(&saved(&id_a)?, &saved(&id_b)?))
+}
+
+/// Lists the saved snapshots, oldest first.
+#[tauri::command]
+fn list_snapshots(state: State<'_, AppState>) -> Result<Vec<SnapshotMeta>, String> {
+    tracing::debug!("list_snapshots command called");
+
+    Ok(state.snapshot_metas())
+}
+
+/// Points the backend at specific `lsof` and `ps` binaries instead of the ones on
+/// PATH. Both are checked to be executable before either is replaced.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn configure_paths(lsof: String, ps: String) -> Result<(), String> {
+    tracing::debug!("configure_paths command called with lsof {:?}, ps {:?}", lsof, ps);
+
+    let lsof = tools::check_executable(&lsof)?;
+    let ps = tools::check_executable(&ps)?;
+    tools::configure(lsof, ps);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn configure_paths(_lsof: String, _ps: String) -> Result<(), String> {
+    Err("lsof and ps are not used on Windows".to_string())
+}
+
+/// Reports where each external binary the backend runs was found and its version,
+/// so a missing `lsof` or `ps` can be flagged before the first listing fails.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn check_dependencies() -> Result<DependencyStatus, String> {
+    tracing::debug!("check_dependencies command called");
+
+    Ok(tools::dependency_status())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn check_dependencies() -> Result<DependencyStatus, String> {
+    Err("lsof and ps are not used on Windows".to_string())
+}
+
+/// Chooses the single-word `ps` columns each listing requests, from the ones it
+/// parses (`PS_WORD_COLUMNS`) plus `EXTRA_PS_COLUMNS`, whose values land in
+/// `ps_extra`. Names are matched against those lists rather than passed to `ps`
+/// as given. `lstart` and `command` are always requested after them, since
+/// grouping needs the command. Dropping a parsed column leaves its field empty.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn set_ps_format(fields: Vec<String>) -> Result<(), String> {
+    tracing::debug!("set_ps_format command called with {:?}", fields);
+
+    let mut columns = Vec::with_capacity(fields.len());
+    for field in &fields {
+        let Some(&column) = PS_WORD_COLUMNS.iter().chain(EXTRA_PS_COLUMNS).find(|&&column| column == field) else {
+            return Err(format!("{:?} is not a supported ps column", field));
+        };
+        if columns.contains(&column) {
+            return Err(format!("{} is listed more than once", column));
+        }
+        columns.push(column);
+    }
+    tools::configure_ps_columns(columns);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn set_ps_format(_fields: Vec<String>) -> Result<(), String> {
+    Err("lsof and ps are not used on Windows".to_string())
+}
+
+/// Sets how long `lsof` and each per-PID `ps` call may run before being killed.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn configure_timeouts(lsof_timeout_ms: u64, ps_timeout_ms: u64) -> Result<(), String> {
+    tracing::debug!(
+        "configure_timeouts command called with lsof {} ms, ps {} ms",
+        lsof_timeout_ms,
+        ps_timeout_ms
+    );
+
+    if lsof_timeout_ms == 0 || ps_timeout_ms == 0 {
+        return Err("Timeouts must be greater than 0".to_string());
+    }
+    tools::configure_timeouts(lsof_timeout_ms, ps_timeout_ms);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn configure_timeouts(_lsof_timeout_ms: u64, _ps_timeout_ms: u64) -> Result<(), String> {
+    Err("lsof and ps are not used on Windows".to_string())
+}
+
+/// Sets how many listings `list_ports` and the other cached commands may collect
+/// at once, and whether calls over the limit wait (`queue_on_busy`) or fail
+/// straight away with a `busy` error.
+#[tauri::command]
+fn configure_collection_limit(
+    state: State<'_, AppState>,
+    max_concurrent: u32,
+    queue_on_busy: bool,
+) -> Result<(), String> {
+    tracing::debug!(
+        "configure_collection_limit command called with max {}, queue {}",
+        max_concurrent,
+        queue_on_busy
+    );
+
+    if max_concurrent == 0 {
+        return Err("At least one collection must be allowed".to_string());
+    }
+    state.configure_collection_limit(max_concurrent, queue_on_busy);
+    Ok(())
+}
+
+/// Reads `config.toml` from the app config directory again and applies it,
+/// returning the settings it holds. A missing file gives the defaults. If any
+/// setting is invalid, none is applied. Logging settings only take effect on
+/// the next launch.
+#[tauri::command]
+fn load_config(state: State<'_, AppState>, config: State<'_, ConfigState>) -> Result<Config, String> {
+    tracing::debug!("load_config command called");
+
+    config.load(&state)
+}
+
+/// Writes the settings in effect, including changes made with commands such as
+/// `set_kill_policy` or `configure_paths`, to `config.toml`.
+#[tauri::command]
+fn save_config(state: State<'_, AppState>, config: State<'_, ConfigState>) -> Result<(), String> {
+    tracing::debug!("save_config command called");
+
+    config.save(&state)
+}
+
+/// Applies the default settings and overwrites `config.toml` with them.
+#[tauri::command]
+fn reset_config(state: State<'_, AppState>, config: State<'_, ConfigState>) -> Result<(), String> {
+    tracing::debug!("reset_config command called");
+
+    config.reset(&state)
+}
+
+/// Forces the next `list_ports` call to collect a fresh listing.
+#[tauri::command]
+fn clear_cache(state: State<'_, AppState>) {
+    tracing::debug!("clear_cache command called");
+
+    state.clear_cache();
+}
+
+/// Lists listening ports for processes owned by `username` only, letting `lsof`
+/// skip everyone else's sockets instead of filtering them afterwards.
+#[tauri::command]
+fn list_ports_for_user(username: String) -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("list_ports_for_user command called for {:?}", username);
+
+    // Reject anything that could be read as another lsof argument
+    let valid = !username.is_empty()
+        && !username.starts_with('-')
+        && username.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        return Err(format!("Invalid username {:?}", username));
+    }
+
+    Ok(collect_listening_ports(Some(&username), |_| true)?)
+}
+
+/// Lists listening ports within `start..=end`. PIDs with a mix of ports keep only
+/// the in-range ones; PIDs with none in range are dropped.
+#[tauri::command]
+fn list_ports_in_range(start: u16, end: u16) -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("list_ports_in_range command called for {}-{}", start, end);
+
+    if start > end {
+        return Err(format!("Invalid port range {}-{}: start is greater than end", start, end));
+    }
+
+    Ok(collect_listening_ports(None, |socket| (start..=end).contains(&socket.port))?)
+}
+
+/// Longest connection timeout `check_process_health` accepts, since the command
+/// blocks until the connection succeeds or times out.
+const MAX_HEALTH_CHECK_TIMEOUT_MS: u64 = 10_000;
+
+/// Checks that `pid` exists, that it listens on TCP `port`, and that a
+/// connection to the port is accepted within `timeout_ms`. The connection goes
+/// to the address the socket is bound to, or to loopback for a wildcard bind.
+#[tauri::command]
+fn check_process_health(pid: u32, port: u16, timeout_ms: u64) -> Result<HealthStatus, String> {
+    tracing::debug!(
+        "check_process_health command called for PID {} on port {} with timeout {} ms",
+        pid, port, timeout_ms
+    );
+
+    if timeout_ms == 0 || timeout_ms > MAX_HEALTH_CHECK_TIMEOUT_MS {
+        return Err(format!("Timeout must be between 1 and {} ms", MAX_HEALTH_CHECK_TIMEOUT_MS));
+    }
+
+    let pid_exists = pid != 0 && is_process_alive(pid);
+    let sockets = listening_tcp_sockets(pid, port).unwrap_or_else(|e| {
+        tracing::warn!("Could not list the sockets of PID {}: {}", pid, e);
+        Vec::new()
+    });
+    // Prefer IPv4 where the process listens on both
+    let target = sockets
+        .iter()
+        .find(|socket| socket.protocol == "TCP")
+        .or(sockets.first())
+        .map_or(IpAddr::V4(Ipv4Addr::LOCALHOST), probe_address);
+
+    let started = Instant::now();
+    let connected = TcpStream::connect_timeout(&SocketAddr::new(target, port), Duration::from_millis(timeout_ms));
+    if let Err(e) = &connected {
+        tracing::debug!("Connecting to {} port {} failed: {}", target, port, e);
+    }
+
+    Ok(HealthStatus {
+        pid_exists,
+        port_open: !sockets.is_empty(),
+        tcp_connectable: connected.is_ok(),
+        latency_ms: connected.ok().map(|_| started.elapsed().as_millis() as u64),
+    })
+}
+
+/// The TCP sockets `pid` listens on at `port`.
+fn listening_tcp_sockets(pid: u32, port: u16) -> Result<Vec<SocketEntry>, String> {
+    #[cfg(target_os = "windows")]
+    let mut sockets = windows::collect_sockets(true)?.0;
+    #[cfg(target_os = "freebsd")]
+    let mut sockets = freebsd::listening_sockets(None)?;
+    #[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+    let mut sockets = lsof_sockets(
+        &["-a", "-p", &pid.to_string(), &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-P", "-n"],
+        true,
+    )?;
+
+    sockets.retain(|socket| socket.pid == pid && socket.port == port && socket.protocol.starts_with("TCP"));
+    Ok(sockets)
+}
+
+/// Where to connect to reach a socket bound to `bind_address`: the address
+/// itself, or loopback of the same family for a wildcard bind.
+fn probe_address(socket: &SocketEntry) -> IpAddr {
+    match socket.bind_address.parse::<IpAddr>() {
+        Ok(ip) if !ip.is_unspecified() => ip,
+        Ok(IpAddr::V4(_)) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        Ok(IpAddr::V6(_)) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        // lsof prints "*" for the wildcard of either family
+        Err(_) if socket.protocol.ends_with('6') => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        Err(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    }
+}
+
+/// Lists the network interfaces and their addresses, up or down.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn list_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
+    tracing::debug!("list_network_interfaces command called");
+
+    interfaces::list()
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn list_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
+    Err("Listing network interfaces is not supported on Windows yet".to_string())
+}
+
+/// Lists open UNIX domain sockets, sorted by process name and then PID.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn list_unix_sockets() -> Result<Vec<UnixSocketInfo>, String> {
+    tracing::debug!("list_unix_sockets command called");
+
+    let stdout = run_lsof(&["-U", "-F", "pcLn"], true)?;
+    let mut sockets = parse_lsof_unix_sockets(&stdout);
+    sockets.sort_by_key(|s| (s.process_name.to_lowercase(), s.pid));
+    Ok(sockets)
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn list_unix_sockets() -> Result<Vec<UnixSocketInfo>, String> {
+    Err("UNIX domain sockets are not listed on Windows".to_string())
+}
+
+/// Lists every open file of `pid`: regular files, directories, devices, pipes
+/// and sockets alike. Fails if the process doesn't exist, rather than
+/// returning an empty list.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_open_files(pid: u32) -> Result<Vec<OpenFileInfo>, String> {
+    tracing::debug!("get_open_files command called for PID {}", pid);
+
+    if pid == 0 || !is_process_alive(pid) {
+        return Err(format!("Process {} not found", pid));
+    }
+    let stdout = run_lsof(&["-p", &pid.to_string(), "-F", "ftDsin"], true)?;
+    Ok(parse_lsof_open_files(&stdout))
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_open_files(_pid: u32) -> Result<Vec<OpenFileInfo>, String> {
+    Err("Listing open files is not supported on Windows".to_string())
+}
+
+/// Lists every internet socket of `pid`, including the established and closing
+/// connections `list_ports` leaves out. The per-PID counterpart of
+/// `find_process_by_port`.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_network_connections(pid: u32) -> Result<Vec<ConnectionInfo>, String> {
+    tracing::debug!("get_network_connections command called for PID {}", pid);
+
+    if pid == 0 || !is_process_alive(pid) {
+        return Err(format!("Process {} not found", pid));
+    }
+    // -a ANDs the selections; otherwise lsof lists network files OR the PID's files
+    let stdout = run_lsof(&["-a", "-i", "-P", "-n", "-p", &pid.to_string(), "-F", "pcftPnT"], true)?;
+    Ok(parse_lsof_connections(&stdout))
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_network_connections(_pid: u32) -> Result<Vec<ConnectionInfo>, String> {
+    Err("Listing connections per process is not supported on Windows".to_string())
+}
+
+/// Groups the PIDs of the `list_ports` listing by the Docker container they run
+/// in, sorted by container ID. Processes outside containers are left out.
+#[tauri::command]
+fn list_containers(state: State<'_, AppState>, provider: State<'_, DynProvider>) -> Result<Vec<ContainerInfo>, String> {
+    tracing::debug!("list_containers command called");
+
+    let mut containers: HashMap<String, ContainerInfo> = HashMap::new();
+    for port_info in state.fresh_ports(|| provider.get_listening_ports())? {
+        for pid_info in port_info.pids {
+            let Some(container_id) = pid_info.container_id else {
+                continue;
+            };
+            let container = containers.entry(container_id.clone()).or_insert_with(|| ContainerInfo {
+                container_id,
+                pids: Vec::new(),
+                process_names: Vec::new(),
+                ports: Vec::new(),
+            });
+            container.pids.push(pid_info.pid);
+            container.process_names.push(port_info.process_name.clone());
+            container.ports.extend(pid_info.ports);
+        }
+    }
+
+    let mut containers: Vec<ContainerInfo> = containers.into_values().collect();
+    for container in &mut containers {
+        container.pids.sort_unstable();
+        container.pids.dedup();
+        container.process_names.sort();
+        container.process_names.dedup();
+        container.ports.sort_unstable();
+        container.ports.dedup();
+    }
+    containers.sort_by(|a, b| a.container_id.cmp(&b.container_id));
+    Ok(containers)
+}
+
+#[tauri::command]
+fn get_system_overview() -> Result<SystemOverview, String> {
+    tracing::debug!("get_system_overview command called");
+
+    system::overview()
+}
+
+#[tauri::command]
+fn list_connections() -> Result<Vec<PortInfo>, String> {
+    tracing::debug!("list_connections command called");
+
+    #[cfg(target_os = "windows")]
+    let (sockets, details_map) = windows::collect_sockets(false)?;
+
+    // lsof is an optional package on FreeBSD, while sockstat ships in base
+    #[cfg(target_os = "freebsd")]
+    let (sockets, details_map) = {
+        let sockets = freebsd::all_sockets()?;
+        let details_map = collect_process_details(sockets.iter().map(|s| s.pid));
+        (sockets, details_map)
+    };
+
+    #[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+    let (sockets, details_map) = {
+        // No state filter: include ESTABLISHED, CLOSE_WAIT, TIME_WAIT, etc. alongside listeners
+        let sockets = lsof_sockets(&["-i", "-P", "-n"], true)?;
+        let details_map = collect_process_details(sockets.iter().map(|s| s.pid));
+        (sockets, details_map)
+    };
+
+    Ok(build_port_info(sockets, &details_map, true))
+}
+
+/// Looks up the process(es) listening on `port` in the `list_ports` listing,
+/// which is indexed by port when it is cached, so only a stale cache runs `lsof`.
+///
+/// IPv4 and IPv6 listeners are both included. If several processes share the
+/// port (e.g. SO_REUSEPORT), their PIDs are all collapsed into one `PortInfo`
+/// named after the first process. Each PID keeps all of its `ports`, not just
+/// `port`.
+#[tauri::command]
+fn find_process_by_port(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    port: u16,
+) -> Result<Option<PortInfo>, String> {
+    tracing::debug!("find_process_by_port command called for port {}", port);
+
+    Ok(state.cached_port(port, || provider.get_listening_ports())?)
+}
+
+/// The service name `port` is known by, from `/etc/services` or the built-in table.
+#[tauri::command]
+fn lookup_port(port: u16) -> Result<Option<String>, String> {
+    tracing::debug!("lookup_port command called for port {}", port);
+
+    Ok(services::port_to_service_name(port))
+}
+
+/// Sets the OOM killer adjustment of `pid`, e.g. -1000 to protect a critical
+/// process. Lowering it below its current value requires root.
+#[tauri::command]
+fn set_oom_score_adj(pid: u32, adj: i32) -> Result<(), String> {
+    tracing::debug!("set_oom_score_adj command called for PID {} with {}", pid, adj);
+
+    if !(-1000..=1000).contains(&adj) {
+        return Err(format!("Invalid OOM score adjustment {}: must be between -1000 and 1000", adj));
+    }
+    inspect::set_oom_score_adj(pid, adj)
+}
+
+/// Returns the CPU indices `pid` is allowed to run on. Linux only.
+#[tauri::command]
+fn get_cpu_affinity(pid: u32) -> Result<Vec<u32>, String> {
+    tracing::debug!("get_cpu_affinity command called for PID: {}", pid);
+
+    inspect::cpu_affinity(pid)
+}
+
+/// Returns memory, CPU and I/O usage of the cgroup at `cgroup_path`, as given by
+/// `PidInfo.cgroup_path`. Linux only.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+fn get_cgroup_stats(cgroup_path: String) -> Result<CgroupStats, String> {
+    tracing::debug!("get_cgroup_stats command called for {}", cgroup_path);
+
+    cgroup::stats(&cgroup_path)
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+fn get_cgroup_stats(_cgroup_path: String) -> Result<CgroupStats, String> {
+    Err("cgroups are only available on Linux".to_string())
+}
+
+/// Returns the scheduling policy and real-time priority of `pid`. Linux only.
+#[tauri::command]
+fn get_scheduler_info(pid: u32) -> Result<SchedulerInfo, String> {
+    tracing::debug!("get_scheduler_info command called for PID: {}", pid);
+
+    inspect::scheduler_info(pid)
+}
+
+/// Pins `pid` to the CPU indices in `cpus`, like `taskset -cp`. Linux only.
+#[tauri::command]
+fn set_cpu_affinity(pid: u32, cpus: Vec<u32>) -> Result<(), String> {
+    tracing::debug!("set_cpu_affinity command called for PID {} with {:?}", pid, cpus);
+
+    if cpus.is_empty() {
+        return Err("At least one CPU must be given".to_string());
+    }
+    inspect::set_cpu_affinity(pid, &cpus)
+}
+
+/// Same as `list_ports`, but also fills `PidInfo.port_labels` with each port
+/// labelled by its service name where one is known, e.g. `["postgres (5432)", "9999"]`.
+#[tauri::command]
+fn list_ports_with_labels(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+) -> Result<Vec<PortInfo>, String> {
+    let mut ports = state.fresh_ports(|| provider.get_listening_ports())?;
+
+    for pid_info in ports.iter_mut().flat_map(|p| p.pids.iter_mut()) {
+        pid_info.port_labels = pid_info
+            .ports
+            .iter()
+            .map(|&port| match services::port_to_service_name(port) {
+                Some(name) => format!("{} ({})", name, port),
+                None => port.to_string(),
+            })
+            .collect();
+    }
+
+    Ok(ports)
+}
+
+/// Lists every zombie process on the system, whether or not it holds sockets.
+///
+/// Zombies have already released their file descriptors, so they are found
+/// via `ps` rather than `lsof`.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn list_zombie_processes() -> Result<Vec<ZombieInfo>, String> {
+    tracing::debug!("list_zombie_processes command called");
+
+    let output = tools::ps()
+        .args(["-axo", "pid=,ppid=,user=,stat=,comm="])
+        .output()
+        .map_err(|e| tools::spawn_error("ps", &e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let zombies = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse::<u32>().ok()?;
+            let ppid = parts.next()?.parse::<u32>().unwrap_or(0);
+            let user = parts.next()?.to_string();
+            if !parts.next()?.starts_with('Z') {
+                return None;
+            }
+            // comm is last because it may contain spaces (a full path on macOS)
+            let process_name = parts.collect::<Vec<_>>().join(" ");
+            Some(ZombieInfo {
+                pid,
+                process_name,
+                ppid,
+                user,
+            })
+        })
+        .collect();
+
+    Ok(zombies)
+}
+
+/// Windows has no zombie state: the kernel reaps exited processes itself.
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn list_zombie_processes() -> Result<Vec<ZombieInfo>, String> {
+    Ok(Vec::new())
+}
+
+/// Clears the zombie `pid` by killing its parent with `kill_process`.
+///
+/// A zombie has already exited, so signalling it does nothing: all that is left
+/// is its process table entry, which stays until the parent collects the exit
+/// status with `wait()`. Once the parent is gone the zombie is re-parented to
+/// init (PID 1), which reaps it straight away. Refuses when the parent is init
+/// itself, since init reaps on its own and must never be killed, or this app.
+#[tauri::command]
+fn kill_zombie_parent(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pid: u32,
+) -> Result<String, String> {
+    tracing::debug!("kill_zombie_parent command called for PID {}", pid);
+
+    let zombie = list_zombie_processes()?
+        .into_iter()
+        .find(|z| z.pid == pid)
+        .ok_or_else(|| format!("Process {} is not a zombie", pid))?;
+    let ppid = killable_zombie_parent(&zombie)?;
+
+    Ok(kill_and_record(&state, &**provider, ppid)?)
+}
+
+/// The parent `kill_zombie_parent` may kill to clear `zombie`.
+fn killable_zombie_parent(zombie: &ZombieInfo) -> Result<u32, String> {
+    if zombie.ppid <= 1 {
+        return Err(format!("Zombie {} belongs to init, which will reap it itself", zombie.pid));
+    }
+    // e.g. a child the app spawned exited before being waited for
+    if zombie.ppid == std::process::id() {
+        return Err(format!("Zombie {} is a child of this app, which will reap it itself", zombie.pid));
+    }
+    Ok(zombie.ppid)
+}
+
+/// How many levels `get_process_tree` descends and `get_process_ancestors`
+/// climbs at most, so a PPID cycle left behind by PID reuse can't make the
+/// walk run forever.
+#[cfg(not(target_os = "windows"))]
+const MAX_TREE_DEPTH: usize = 32;
+
+/// Every (pid, ppid) pair from one `ps -axo pid=,ppid=` snapshot, in `ps` order.
+#[cfg(not(target_os = "windows"))]
+fn read_parent_links() -> Result<Vec<(u32, u32)>, String> {
+    let output = tools::ps()
+        .args(["-axo", "pid=,ppid="])
+        .output()
+        .map_err(|e| tools::spawn_error("ps", &e))?;
+
+    let links = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+        })
+        .collect();
+
+    Ok(links)
+}
+
+/// Maps each PID to its direct children using one `ps -axo pid=,ppid=` snapshot.
+#[cfg(not(target_os = "windows"))]
+fn read_process_children() -> Result<HashMap<u32, Vec<u32>>, String> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (pid, ppid) in read_parent_links()? {
+        children.entry(ppid).or_default().push(pid);
+    }
+
+    Ok(children)
+}
+
+/// Returns `pid` and its ancestors, leaf first, up to the process whose parent
+/// is 0: init (PID 1, `launchd` on macOS) for every user process, `kthreadd`
+/// (PID 2) for Linux kernel threads. Parents come from one `ps` snapshot, so
+/// the chain is consistent even while processes exit. A walk longer than
+/// `MAX_TREE_DEPTH` stops there.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_process_ancestors(pid: u32) -> Result<Vec<AncestorInfo>, String> {
+    tracing::debug!("get_process_ancestors command called for PID: {}", pid);
+
+    if !is_process_alive(pid) {
+        return Err(format!("Process {} not found", pid));
+    }
+
+    let parents: HashMap<u32, u32> = read_parent_links()?.into_iter().collect();
+    let mut chain = Vec::new();
+    let mut current = Some(pid);
+    while let Some(pid) = current {
+        if chain.len() == MAX_TREE_DEPTH {
+            tracing::warn!("Stopping the ancestor walk after {} levels", MAX_TREE_DEPTH);
+            break;
+        }
+        let (process_name, command) = audit_identity(pid);
+        chain.push(AncestorInfo {
+            pid,
+            process_name,
+            command,
+        });
+        // Gone from the snapshot (exited just now) or reached the top
+        current = parents.get(&pid).copied().filter(|&ppid| ppid != 0 && pid != 1);
+    }
+
+    Ok(chain)
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_process_ancestors(_pid: u32) -> Result<Vec<AncestorInfo>, String> {
+    Err("Process trees are not supported on Windows yet".to_string())
+}
+
+/// Returns `root_pid` followed by all of its descendants, breadth-first.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_process_tree(root_pid: u32) -> Result<Vec<PidInfo>, String> {
+    tracing::debug!("get_process_tree command called for PID: {}", root_pid);
+
+    if !is_process_alive(root_pid) {
+        return Err(format!("Process {} not found", root_pid));
+    }
+
+    let (order, _) = walk_descendants(&read_process_children()?, root_pid);
+
+    let mut details_map = collect_process_details(order.iter().copied());
+    let tree = order
+        .into_iter()
+        .map(|pid| process_node(pid, &mut details_map))
+        .collect();
+
+    Ok(tree)
+}
+
+/// Returns `root_pid` with all of its descendants nested under their parents,
+/// whether or not they hold sockets. Like `get_process_tree`, the tree is cut
+/// off `MAX_TREE_DEPTH` levels down.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_descendant_tree(pid: u32) -> Result<ProcessTree, String> {
+    tracing::debug!("get_descendant_tree command called for PID: {}", pid);
+
+    if !is_process_alive(pid) {
+        return Err(format!("Process {} not found", pid));
+    }
+
+    let (order, tree_children) = walk_descendants(&read_process_children()?, pid);
+    let mut details_map = collect_process_details(order);
+    Ok(build_process_tree(pid, &tree_children, &mut details_map))
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_descendant_tree(_pid: u32) -> Result<ProcessTree, String> {
+    Err("Process trees are not supported on Windows yet".to_string())
+}
+
+/// Walks `children` breadth-first from `root_pid`, at most `MAX_TREE_DEPTH`
+/// levels down and visiting each PID once. Returns the PIDs in visit order and,
+/// per PID, the children it was reached through, which form a proper tree even
+/// if PID reuse left a cycle in `children`.
+#[cfg(not(target_os = "windows"))]
+fn walk_descendants(children: &HashMap<u32, Vec<u32>>, root_pid: u32) -> (Vec<u32>, HashMap<u32, Vec<u32>>) {
+    let mut visited: HashSet<u32> = HashSet::from([root_pid]);
+    let mut order = vec![root_pid];
+    let mut tree_children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut level = vec![root_pid];
+
+    for _ in 0..MAX_TREE_DEPTH {
+        let mut next = Vec::new();
+        for &pid in &level {
+            for &child in children.get(&pid).into_iter().flatten() {
+                if visited.insert(child) {
+                    tree_children.entry(pid).or_default().push(child);
+                    next.push(child);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        order.extend(&next);
+        level = next;
+    }
+
+    (order, tree_children)
+}
+
+/// A `PidInfo` for a process looked up by PID rather than through a socket.
+#[cfg(not(target_os = "windows"))]
+fn process_node(pid: u32, details_map: &mut HashMap<u32, ProcessDetails>) -> PidInfo {
+    let details = details_map.remove(&pid).unwrap_or_default();
+    PidInfo::from_details(pid, String::new(), String::new(), Vec::new(), String::new(), details)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn build_process_tree(
+    pid: u32,
+    tree_children: &HashMap<u32, Vec<u32>>,
+    details_map: &mut HashMap<u32, ProcessDetails>,
+) -> ProcessTree {
+    let node = process_node(pid, details_map);
+    let children = tree_children
+        .get(&pid)
+        .into_iter()
+        .flatten()
+        .map(|&child| build_process_tree(child, tree_children, details_map))
+        .collect();
+    ProcessTree { node, children }
+}
+
+/// Everything `PidInfo` can say about `pid`, including the lookups too slow for
+/// `list_ports` such as the working directory and the launchd label. Socket
+/// fields are left empty.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_process_detail(pid: u32) -> Result<PidInfo, String> {
+    tracing::debug!("get_process_detail command called for PID: {}", pid);
+
+    if pid == 0 || !is_process_alive(pid) {
+        return Err(format!("Process {} not found", pid));
+    }
+
+    let mut details = collect_process_details([pid]).remove(&pid).unwrap_or_default();
+    fill_slow_details(pid, &mut details);
+    let mut pid_info = PidInfo::from_details(pid, String::new(), String::new(), Vec::new(), String::new(), details);
+    pid_info.launchd_label = inspect::launchd_label(pid);
+    #[cfg(target_os = "macos")]
+    {
+        if !pid_info.exe_path.is_empty() {
+            (pid_info.is_signed, pid_info.signing_authority) = security::signature(&pid_info.exe_path);
+        }
+        if let Some((rx, tx)) = inspect::net_io(pid) {
+            (pid_info.net_rx_bytes, pid_info.net_tx_bytes) = (rx, tx);
+        }
+    }
+    Ok(pid_info)
+}
+
+/// Reports whether the executable of `pid` is code signed, by whom, and whether
+/// it is sandboxed. Runs `codesign` twice, so it isn't part of `list_ports`.
+#[cfg(target_os = "macos")]
+#[tauri::command]
+fn get_process_security(pid: u32) -> Result<ProcessSecurity, String> {
+    tracing::debug!("get_process_security command called for PID: {}", pid);
+
+    security::process_security(pid)
+}
+
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+fn get_process_security(_pid: u32) -> Result<ProcessSecurity, String> {
+    Err("Code signing status is only available on macOS".to_string())
+}
+
+/// Longest sampling window `get_network_io_rate` accepts, since the command
+/// blocks for the whole window.
+#[cfg(not(target_os = "windows"))]
+const MAX_NETWORK_IO_WINDOW_MS: u64 = 10_000;
+
+/// Measures the network throughput of `pid` by reading its byte counters twice,
+/// `window_ms` apart. See `PidInfo::net_rx_bytes` for what the counters cover.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn get_network_io_rate(pid: u32, window_ms: u64) -> Result<NetworkIORate, String> {
+    tracing::debug!("get_network_io_rate command called for PID {} over {} ms", pid, window_ms);
+
+    if window_ms == 0 || window_ms > MAX_NETWORK_IO_WINDOW_MS {
+        return Err(format!("Window must be between 1 and {} ms", MAX_NETWORK_IO_WINDOW_MS));
+    }
+    let read = || inspect::net_io(pid).ok_or_else(|| format!("Failed to read network counters of process {}", pid));
+
+    let started = Instant::now();
+    let (rx_before, tx_before) = read()?;
+    thread::sleep(Duration::from_millis(window_ms));
+    let (rx_after, tx_after) = read()?;
+
+    // Measured rather than assumed, since each nettop sample takes a while itself
+    let elapsed = started.elapsed().as_secs_f64();
+    Ok(NetworkIORate {
+        pid,
+        window_ms,
+        rx_bytes_per_sec: rx_after.saturating_sub(rx_before) as f64 / elapsed,
+        tx_bytes_per_sec: tx_after.saturating_sub(tx_before) as f64 / elapsed,
+    })
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_network_io_rate(_pid: u32, _window_ms: u64) -> Result<NetworkIORate, String> {
+    Err("Network I/O counters are not supported on Windows yet".to_string())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_process_detail(_pid: u32) -> Result<PidInfo, String> {
+    Err("Detailed process info is not supported on Windows yet".to_string())
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn get_process_tree(_root_pid: u32) -> Result<Vec<PidInfo>, String> {
+    Err("Process trees are not supported on Windows yet".to_string())
+}
+
+#[tauri::command]
+fn get_process_cwd(pid: u32) -> Result<String, String> {
+    tracing::debug!("get_process_cwd command called for PID: {}", pid);
+
+    inspect::cwd(pid)
+}
+
+/// Returns the address space layout of `pid`, via `pmap -x` on Linux and
+/// `vmmap -summary` on macOS. A process that exits while the tool is reading
+/// it gives whatever was read (possibly nothing) and a warning, not an error.
+#[tauri::command]
+fn get_memory_map(pid: u32) -> Result<Vec<MemoryRegion>, String> {
+    tracing::debug!("get_memory_map command called for PID: {}", pid);
+
+    if pid == 0 || !is_process_alive(pid) {
+        return Err(format!("Process {} not found", pid));
+    }
+
+    match inspect::memory_map(pid) {
+        Ok(regions) if regions.is_empty() && !is_process_alive(pid) => {
+            tracing::warn!("Process {} exited while its memory map was read", pid);
+            Ok(regions)
+        }
+        Err(e) if !is_process_alive(pid) => {
+            tracing::warn!("Process {} exited while its memory map was read: {}", pid, e);
+            Ok(Vec::new())
+        }
+        result => result,
+    }
+}
+
+/// Lists the shared libraries `pid` has loaded (`.so` files on Linux, `.dylib`s
+/// and frameworks on macOS), once per path, for spotting injected or outdated ones.
+#[tauri::command]
+fn get_shared_libraries(pid: u32) -> Result<Vec<SharedLib>, String> {
+    tracing::debug!("get_shared_libraries command called for PID: {}", pid);
+
+    inspect::shared_libraries(pid)
+}
+
+/// Returns the cumulative disk I/O of `pid`. Linux only.
+#[tauri::command]
+fn get_disk_io(pid: u32) -> Result<DiskIO, String> {
+    tracing::debug!("get_disk_io command called for PID: {}", pid);
+
+    inspect::disk_io(pid)
+}
+
+/// How full the descriptor table may get before `get_process_resource_limits`
+/// sets `open_files_near_limit`, in percent of the soft limit.
+const OPEN_FILES_WARNING_PERCENT: u64 = 90;
+
+/// Returns the resource limits of `pid` and how many descriptors it has open.
+/// On macOS the limits are the system defaults from `launchctl limit`, which
+/// a process may have changed for itself.
+#[tauri::command]
+fn get_process_resource_limits(pid: u32) -> Result<ResourceLimits, String> {
+    tracing::debug!("get_process_resource_limits command called for PID: {}", pid);
+
+    let mut limits = inspect::resource_limits(pid)?;
+    limits.open_files_near_limit = match (limits.open_files, limits.max_open_files) {
+        (Some(open), Some(max)) => u64::from(open) * 100 >= max.saturating_mul(OPEN_FILES_WARNING_PERCENT),
+        _ => false,
+    };
+    Ok(limits)
+}
+
+/// The open-file and process limits of `pid` with sizes in KiB, a narrower view
+/// of `get_process_resource_limits`.
+#[tauri::command]
+fn get_process_limits(pid: u32) -> Result<ProcessLimits, String> {
+    tracing::debug!("get_process_limits command called for PID: {}", pid);
+
+    get_process_resource_limits(pid).map(ProcessLimits::from)
+}
+
+/// Returns the full argv of `pid`, which `PidInfo.command` may truncate.
+#[tauri::command]
+fn get_process_args(pid: u32) -> Result<Vec<String>, String> {
+    tracing::debug!("get_process_args command called for PID: {}", pid);
+
+    inspect::args(pid)
+}
+
+/// Replaces the values of sensitive variables in `get_process_env` results.
+const REDACTED: &str = "[REDACTED]";
+
+/// Returns the environment of `pid`, with the values of variables matching the
+/// `AppState` blocklist replaced by `"[REDACTED]"`.
+#[tauri::command]
+fn get_process_env(state: State<'_, AppState>, pid: u32) -> Result<HashMap<String, String>, String> {
+    tracing::debug!("get_process_env command called for PID: {}", pid);
+
+    let mut env = inspect::environ(pid)?;
+    for (name, value) in env.iter_mut() {
+        if state.is_sensitive_env_var(name) {
+            *value = REDACTED.to_string();
+        }
+    }
+    Ok(env)
+}
+
+/// Starts polling `list_ports` every `interval_ms` in the background and emitting
+/// `"process-update"` events with the groups that changed. Restarts the monitor
+/// if it is already running.
+#[tauri::command]
+fn start_monitor(app: AppHandle, state: State<'_, MonitorState>, interval_ms: u64) -> Result<(), String> {
+    tracing::debug!("start_monitor command called with interval {} ms", interval_ms);
+
+    if interval_ms == 0 {
+        return Err("Monitor interval must be greater than 0 ms".to_string());
+    }
+
+    state.start(app, Duration::from_millis(interval_ms));
+    Ok(())
+}
+
+/// Serves `GET /ports` and `POST /kill` on `127.0.0.1:<port>` for scripts,
+/// behind a bearer token written to `~/.process-monitor/api_token`. Restarts
+/// the server with a new token if it is already running.
+#[tauri::command]
+fn start_api_server(app: AppHandle, state: State<'_, ApiServerState>, port: u16) -> Result<(), String> {
+    tracing::debug!("start_api_server command called with port {}", port);
+
+    if port == 0 {
+        return Err("API server port must be greater than 0".to_string());
+    }
+
+    state.start(app, port)
+}
+
+#[tauri::command]
+fn stop_api_server(state: State<'_, ApiServerState>) -> Result<(), String> {
+    tracing::debug!("stop_api_server command called");
+
+    if state.stop() {
+        Ok(())
+    } else {
+        Err("API server is not running".to_string())
+    }
+}
+
+#[tauri::command]
+fn stop_monitor(state: State<'_, MonitorState>) -> Result<(), String> {
+    tracing::debug!("stop_monitor command called");
+
+    if state.stop() {
+        Ok(())
+    } else {
+        Err("Monitor is not running".to_string())
+    }
+}
+
+/// Starts checking `list_ports` every `interval_ms` in the background and emitting
+/// a `"threshold-exceeded"` event for each PID whose `cpu` or `mem` is above the
+/// given percentages, at most once per PID every 10 seconds. Restarts the watcher
+/// if it is already running.
+#[tauri::command]
+fn watch_thresholds(
+    app: AppHandle,
+    state: State<'_, ThresholdWatchState>,
+    cpu_percent: f32,
+    mem_percent: f32,
+    interval_ms: u64,
+) -> Result<(), String> {
+    tracing::debug!(
+        "watch_thresholds command called with cpu {}%, mem {}%, interval {} ms",
+        cpu_percent, mem_percent, interval_ms
+    );
+
+    if interval_ms == 0 {
+        return Err("Watch interval must be greater than 0 ms".to_string());
+    }
+    if !(cpu_percent >= 0.0 && mem_percent >= 0.0) {
+        return Err("Thresholds must be non-negative percentages".to_string());
+    }
+
+    state.start(app, cpu_percent, mem_percent, Duration::from_millis(interval_ms));
+    Ok(())
+}
+
+#[tauri::command]
+fn unwatch_thresholds(state: State<'_, ThresholdWatchState>) -> Result<(), String> {
+    tracing::debug!("unwatch_thresholds command called");
+
+    if state.stop() {
+        Ok(())
+    } else {
+        Err("Threshold watcher is not running".to_string())
+    }
+}
+
+/// Starts polling `list_ports` every `interval_ms` in the background and emitting
+/// `"port-opened"` / `"port-closed"` events for each port binding that appears or
+/// disappears. Restarts the watcher if it is already running.
+///
+/// With `max_interval_ms`, the interval doubles after each poll that finds no
+/// change, up to that cap, and drops back to `interval_ms` as soon as one does.
+#[tauri::command]
+fn watch_ports(
+    app: AppHandle,
+    state: State<'_, PortWatchState>,
+    interval_ms: u64,
+    max_interval_ms: Option<u64>,
+) -> Result<(), String> {
+    tracing::debug!(
+        "watch_ports command called with interval {} ms, max interval {:?} ms",
+        interval_ms, max_interval_ms
+    );
+
+    if interval_ms == 0 {
+        return Err("Watch interval must be greater than 0 ms".to_string());
+    }
+    let min = Duration::from_millis(interval_ms);
+    let backoff = match max_interval_ms {
+        Some(max_interval_ms) if max_interval_ms < interval_ms => {
+            return Err("Maximum watch interval must be at least the watch interval".to_string());
+        }
+        Some(max_interval_ms) => Backoff::between(min, Duration::from_millis(max_interval_ms)),
+        None => Backoff::fixed(min),
+    };
+
+    state.start(app, backoff);
+    Ok(())
+}
+
+/// Returns the port watcher's current interval and whether its recent polls
+/// found changes, for checking how far `watch_ports` has backed off.
+#[tauri::command]
+fn get_monitor_status(state: State<'_, PortWatchState>) -> Result<MonitorStatus, String> {
+    tracing::debug!("get_monitor_status command called");
+
+    Ok(state.status())
+}
+
+#[tauri::command]
+fn unwatch_ports(state: State<'_, PortWatchState>) -> Result<(), String> {
+    tracing::debug!("unwatch_ports command called");
+
+    if state.stop() {
+        Ok(())
+    } else {
+        Err("Port watcher is not running".to_string())
+    }
+}
+
+/// Highest signal number accepted by `send_signal` (Linux real-time signals end at 64).
+const MAX_SIGNAL: i32 = 64;
+
+/// Why a signal could not be delivered.
+#[derive(Debug)]
+enum SignalError {
+    /// The `kill` tool itself could not be run, so no PID can be signalled.
+    Spawn(String),
+    /// `kill` ran but rejected this PID (e.g. no such process, not permitted).
+    Failed(String),
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalError::Spawn(message) | SignalError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Delivers `signal` to `pid` with `kill -<signal> <pid>`.
+#[cfg(not(target_os = "windows"))]
+fn deliver_signal(pid: u32, signal: i32) -> Result<(), SignalError> {
+    run_kill(signal, &pid.to_string())
+}
+
+/// Delivers `signal` to every member of process group `pgid` with `kill -<signal> -- -<pgid>`.
+#[cfg(not(target_os = "windows"))]
+fn deliver_group_signal(pgid: u32, signal: i32) -> Result<(), SignalError> {
+    run_kill(signal, &format!("-{}", pgid))
+}
+
+/// One row of `ps -axo pid=,pgid=[,sid=]`.
+#[cfg(not(target_os = "windows"))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProcessIds {
+    pid: u32,
+    pgid: u32,
+    sid: Option<u32>,
+}
+
+/// Every process's group (and, with `with_sid`, session) from one `ps` snapshot.
+/// `sid` is a Linux-only column.
+#[cfg(not(target_os = "windows"))]
+fn process_ids(with_sid: bool) -> Result<Vec<ProcessIds>, String> {
+    let columns = if with_sid { "pid=,pgid=,sid=" } else { "pid=,pgid=" };
+    let output = tools::ps()
+        .args(["-axo", columns])
+        .output()
+        .map_err(|e| tools::spawn_error("ps", &e))?;
+    Ok(parse_process_ids(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses rows of two or three whitespace-separated IDs, skipping malformed ones.
+#[cfg(not(target_os = "windows"))]
+fn parse_process_ids(stdout: &str) -> Vec<ProcessIds> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut ids = line.split_whitespace().map(|id| id.parse::<u32>().ok());
+            Some(ProcessIds {
+                pid: ids.next()??,
+                pgid: ids.next()??,
+                sid: ids.next().flatten(),
+            })
+        })
+        .collect()
+}
+
+/// Checks that group `pgid` may be signalled. kill(2) reads group 0 as the
+/// caller's own group and -1 as every process it may signal, so groups 0 and 1
+/// are refused, as is the app's own group. Every member must also pass the kill
+/// policy. `table` is a `process_ids` snapshot.
+#[cfg(not(target_os = "windows"))]
+fn check_group_signallable(state: &AppState, pgid: u32, table: &[ProcessIds]) -> Result<(), String> {
+    match pgid {
+        0 => return Err("Process group ID must be nonzero".to_string()),
+        1 => return Err("Refusing to signal process group 1, which kill reads as every process".to_string()),
+        _ => {}
+    }
+    let own_pid = std::process::id();
+    if table.iter().any(|ids| ids.pid == own_pid && ids.pgid == pgid) {
+        return Err(format!("Refusing to signal process group {}, which the app belongs to", pgid));
+    }
+
+    let mut members = table.iter().filter(|ids| ids.pgid == pgid).peekable();
+    if members.peek().is_none() {
+        return Err(format!("Process group {} not found", pgid));
+    }
+    for ids in members {
+        check_kill_policy(state, ids.pid)?;
+    }
+    Ok(())
+}
+
+/// Sends `signal` to group `pgid` after `check_group_signallable`, recording
+/// the attempt in the audit log under the group leader.
+#[cfg(not(target_os = "windows"))]
+fn signal_group(state: &AppState, pgid: u32, signal: i32, table: &[ProcessIds]) -> Result<(), String> {
+    let (process_name, command) = audit_identity(pgid);
+    let result = check_group_signallable(state, pgid, table)
+        .and_then(|()| deliver_group_signal(pgid, signal).map_err(|e| e.to_string()));
+    let error = result.as_ref().err().cloned();
+    record_kill(state, KillLogEntry::group(pgid, signal, process_name, command, error));
+    result
+}
+
+/// Runs `kill -<signal> -- <target>`; `--` keeps negative group targets from being read as options.
+#[cfg(not(target_os = "windows"))]
+fn run_kill(signal: i32, target: &str) -> Result<(), SignalError> {
+    let output = Command::new("kill")
+        .args([&format!("-{}", signal), "--", target])
+        .output()
+        .map_err(|e| {
+            tracing::warn!("Failed to execute kill command: {}", e);
+            SignalError::Spawn(format!("Failed to execute kill: {}", e))
+        })?;
+
+    tracing::debug!("Kill command exit status: {}", output.status);
+    tracing::debug!("Kill command stdout: {}", String::from_utf8_lossy(&output.stdout));
+    tracing::debug!("Kill command stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SignalError::Failed(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+/// Returns whether `pid` still exists, using signal 0 which checks without delivering anything.
+#[cfg(not(target_os = "windows"))]
+fn is_process_alive(pid: u32) -> bool {
+    deliver_signal(pid, 0).is_ok()
+}
+
+fn validate_signal(signal: i32) -> Result<(), String> {
+    // Signal 0 is allowed: it only checks that the process exists
+    if (0..=MAX_SIGNAL).contains(&signal) {
+        Ok(())
+    } else {
+        Err(format!("Invalid signal {}: must be between 0 and {}", signal, MAX_SIGNAL))
+    }
+}
+
+/// Turns the outcome of `deliver_signal` into the messages shown to the frontend.
+fn describe_signal_result(pid: u32, signal: i32, result: Result<(), SignalError>) -> Result<String, String> {
+    match result {
+        Ok(()) => {
+            tracing::info!("Signal {} sent to process {}", signal, pid);
+            Ok(format!("Signal {} sent to process {}", signal, pid))
+        }
+        Err(e) => {
+            let err_msg = format!("Failed to send signal {} to process {}: {}", signal, pid, e);
+            tracing::warn!("{}", err_msg);
+            Err(err_msg)
+        }
+    }
+}
+
+/// Sends `signal` to `pid` and records the attempt in the audit log.
+#[tauri::command]
+fn send_signal(state: State<'_, AppState>, pid: u32, signal: i32) -> Result<String, String> {
+    tracing::debug!("Sending signal {} to process with PID: {}", signal, pid);
+
+    send_audited_signal(&state, pid, signal)
+}
+
+/// Validates `pid` and `signal`, checks the kill policy and delivers the signal.
+/// Every path that signals a single PID without a provider goes through here.
+fn signal_allowed(state: &AppState, pid: u32, signal: i32) -> Result<String, String> {
+    // PID 0 would mean "our own process group" to kill(2)
+    if pid == 0 {
+        return Err("PID must be nonzero".to_string());
+    }
+    validate_signal(signal)?;
+    check_kill_policy(state, pid)?;
+    describe_signal_result(pid, signal, deliver_signal(pid, signal))
+}
+
+/// Sends `signal` to `pid` after `signal_allowed`'s checks, recording the attempt in the audit log.
+fn send_audited_signal(state: &AppState, pid: u32, signal: i32) -> Result<String, String> {
+    let (process_name, command) = audit_identity(pid);
+    let result = signal_allowed(state, pid, signal);
+    let error = result.as_ref().err().cloned();
+    record_kill(state, KillLogEntry::new(pid, signal, process_name, command, error));
+    result
+}
+
+/// The name and command line of `pid` for its audit log entry, looked up before
+/// signalling it since once the signal lands the process may be gone.
+fn audit_identity(pid: u32) -> (String, String) {
+    let process_name = inspect::name(pid).unwrap_or_default();
+    let command = inspect::args(pid).map(|args| args.join(" ")).unwrap_or_default();
+    (process_name, command)
+}
+
+/// Appends `entry` to the audit log. A log that can't be written never fails the kill itself.
+fn record_kill(state: &AppState, entry: KillLogEntry) {
+    if let Err(e) = state.audit_log().record(&entry) {
+        tracing::warn!("{}", e);
+    }
+}
+
+/// Returns the last `limit` entries of the kill audit log, newest first.
+#[tauri::command]
+fn get_kill_history(state: State<'_, AppState>, limit: usize) -> Result<Vec<KillLogEntry>, String> {
+    tracing::debug!("get_kill_history command called with limit {}", limit);
+
+    state.audit_log().last_entries(limit)
+}
+
+/// Returns the kill policy in effect.
+#[tauri::command]
+fn get_kill_policy(state: State<'_, AppState>) -> KillPolicy {
+    tracing::debug!("get_kill_policy command called");
+
+    state.kill_policy()
+}
+
+/// Replaces the kill policy. It lasts until the app exits unless `save_config`
+/// writes it to the config file.
+#[tauri::command]
+fn set_kill_policy(state: State<'_, AppState>, policy: KillPolicy) -> Result<(), String> {
+    tracing::debug!(
+        "set_kill_policy command called with {} protected names and {} protected users",
+        policy.protected_names.len(),
+        policy.protected_users.len()
+    );
+
+    policy.validate()?;
+    state.set_kill_policy(policy);
+    Ok(())
+}
+
+/// A PID paired with the result of signalling it.
+type SignalOutcome = (u32, Result<String, String>);
+
+/// Sends `signal` to all `pids` in parallel, one `kill` per PID, making the
+/// same checks and audit log entries as `kill_process`.
+///
+/// Only fails outright when the signal is invalid; per-PID failures, including
+/// PIDs the kill policy protects, are reported alongside each PID.
+#[tauri::command]
+fn kill_processes(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pids: Vec<u32>,
+    signal: i32,
+) -> Result<Vec<SignalOutcome>, String> {
+    tracing::debug!("Sending signal {} to {} processes", signal, pids.len());
+
+    validate_signal(signal)?;
+
+    let results = signal_and_record(&state, &**provider, &pids, signal);
+    Ok(pids.into_iter().zip(results).map(|(pid, result)| (pid, result.map_err(String::from))).collect())
+}
+
+/// Kills `pid`, records the attempt in the audit log and drops the cached
+/// `list_ports` listing, which no longer reflects the running processes
+/// whether or not the kill succeeded. Runs off the IPC thread like `list_ports`,
+/// since the existence check and the kill each spawn a tool.
+#[tauri::command]
+async fn kill_process(app: AppHandle, pid: u32) -> Result<String, KillError> {
+    tracing::debug!("Attempting to kill process with PID: {}", pid);
+
+    tauri::async_runtime::spawn_blocking(move || kill_and_record(&app.state(), &**app.state::<DynProvider>(), pid))
+        .await
+        .map_err(|e| KillError::SpawnError(format!("Kill task failed: {}", e)))?
+}
+
+/// What `kill_process` does, for the commands that kill as one of their steps.
+fn kill_and_record(
+    state: &AppState,
+    provider: &(dyn ProcessInfoProvider + Sync),
+    pid: u32,
+) -> Result<String, KillError> {
+    signal_and_record(state, provider, &[pid], 9).pop().expect("one result per PID")
+}
+
+/// Signals each of `pids` after the kill policy and `check_signallable`,
+/// recording every attempt in the audit log, then drops the cached listing
+/// once. At most `available_parallelism()` PIDs are signalled at a time, and
+/// the results come back in the order of `pids`.
+fn signal_and_record(
+    state: &AppState,
+    provider: &(dyn ProcessInfoProvider + Sync),
+    pids: &[u32],
+    signal: i32,
+) -> Vec<Result<String, KillError>> {
+    let signal_one = |pid: u32| {
+        let (process_name, command) = audit_identity(pid);
+        let result = check_kill_policy(state, pid).and_then(|()| signal_existing_pid(provider, pid, signal));
+        let error = result.as_ref().err().map(KillError::to_string);
+        record_kill(state, KillLogEntry::new(pid, signal, process_name, command, error));
+        result
+    };
+
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut results = Vec::with_capacity(pids.len());
+    for chunk in pids.chunks(workers) {
+        thread::scope(|scope| {
+            let handles: Vec<_> = chunk.iter().map(|&pid| scope.spawn(move || signal_one(pid))).collect();
+            for handle in handles {
+                results.push(handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)));
+            }
+        });
+    }
+
+    state.invalidate_cache();
+    results
+}
+
+/// Checks with signal 0 that `pid` exists and may be signalled, so a process
+/// that already exited is reported as `ProcessNotFound` rather than as whatever
+/// `kill` printed.
+fn check_signallable(provider: &dyn ProcessInfoProvider, pid: u32) -> Result<(), KillError> {
+    // PID 0 would mean "our own process group" to kill(2), which signal 0 happily accepts
+    if pid == 0 {
+        return Err(KillError::ProcessNotFound(pid));
+    }
+
+    provider.kill_pid(pid, 0).map_err(|e| match e {
+        // Signal 0 fails with EPERM for processes that exist but aren't ours
+        error @ (KillError::SpawnError(_) | KillError::PermissionDenied(_)) => error,
+        _ => KillError::ProcessNotFound(pid),
+    })
+}
+
+/// Fails with `PolicyViolation` if the kill policy protects `pid`. A name or
+/// owner that can't be read matches nothing, so such a process stays killable.
+fn check_kill_policy(state: &AppState, pid: u32) -> Result<(), KillError> {
+    let policy = state.kill_policy();
+    let name = inspect::name(pid).ok();
+    // Only spawn ps for the owner when some user is protected
+    let user = if policy.protected_users.is_empty() {
+        None
+    } else {
+        inspect::user(pid).ok()
+    };
+
+    match policy.violation(name.as_deref(), user.as_deref()) {
+        Some(reason) => {
+            let error = KillError::PolicyViolation { pid, reason };
+            tracing::warn!("{}", error);
+            Err(error)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Sends `signal` to `pid` after `check_signallable`.
+fn signal_existing_pid(provider: &dyn ProcessInfoProvider, pid: u32, signal: i32) -> Result<String, KillError> {
+    let log = |error: KillError| {
+        tracing::warn!("{}", error);
+        error
+    };
+
+    check_signallable(provider, pid).map_err(log)?;
+
+    provider.kill_pid(pid, signal).map_err(|e| {
+        log(match e {
+            KillError::ProcessNotFound(pid) => KillError::RaceCondition(pid),
+            error => error,
+        })
+    })?;
+
+    if signal == 9 {
+        tracing::info!("Process {} killed", pid);
+        Ok(format!("Process {} killed successfully", pid))
+    } else {
+        tracing::info!("Signal {} sent to process {}", signal, pid);
+        Ok(format!("Signal {} sent to process {}", signal, pid))
+    }
+}
+
+/// Runs the checks `kill_process` makes before SIGKILL without sending it, for
+/// demos and tests of the kill flow. Nothing is logged to the audit log.
+#[tauri::command]
+fn kill_process_dry_run(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pid: u32,
+) -> Result<String, String> {
+    tracing::debug!("kill_process_dry_run command called for PID {}", pid);
+
+    check_kill_policy(&state, pid)?;
+    check_signallable(provider.as_ref(), pid)?;
+    Ok(format!("DRY RUN: would kill PID {}", pid))
+}
+
+/// SIGSTOP and SIGCONT, whose numbers (unlike SIGTERM and SIGKILL) differ
+/// between Linux and the BSD-derived macOS.
+#[cfg(target_os = "linux")]
+const SIGSTOP: i32 = 19;
+#[cfg(target_os = "linux")]
+const SIGCONT: i32 = 18;
+#[cfg(not(target_os = "linux"))]
+const SIGSTOP: i32 = 17;
+#[cfg(not(target_os = "linux"))]
+const SIGCONT: i32 = 19;
+
+/// Pauses `pid` with SIGSTOP. It shows up with `process_state` `"T"` until
+/// `resume_process` is called.
+#[tauri::command]
+fn suspend_process(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pid: u32,
+) -> Result<String, KillError> {
+    tracing::debug!("suspend_process command called for PID {}", pid);
+
+    signal_existing(&state, provider.as_ref(), pid, SIGSTOP)?;
+    Ok(format!("Process {} suspended", pid))
+}
+
+/// Continues `pid` after `suspend_process` with SIGCONT.
+#[tauri::command]
+fn resume_process(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pid: u32,
+) -> Result<String, KillError> {
+    tracing::debug!("resume_process command called for PID {}", pid);
+
+    signal_existing(&state, provider.as_ref(), pid, SIGCONT)?;
+    Ok(format!("Process {} resumed", pid))
+}
+
+/// Sends `signal` to `pid` after `check_signallable` and the kill policy, records
+/// the attempt in the audit log and drops the cached listing, whose
+/// `process_state` is now stale.
+fn signal_existing(
+    state: &AppState,
+    provider: &dyn ProcessInfoProvider,
+    pid: u32,
+    signal: i32,
+) -> Result<(), KillError> {
+    check_signallable(provider, pid)?;
+
+    let (process_name, command) = audit_identity(pid);
+    let result = check_kill_policy(state, pid).and_then(|()| provider.kill_pid(pid, signal));
+    let error = result.as_ref().err().map(KillError::to_string);
+    record_kill(state, KillLogEntry::new(pid, signal, process_name, command, error));
+    state.invalidate_cache();
+
+    result.inspect_err(|e| tracing::warn!("{}", e))
+}
+
+/// Returns the PIDs with a TCP listener on `port`.
+fn listening_pids(port: u16) -> Result<Vec<u32>, String> {
+    #[cfg(target_os = "windows")]
+    let pids = {
+        let (sockets, _) = windows::collect_sockets(true)?;
+        let mut pids: Vec<u32> = sockets
+            .into_iter()
+            .filter(|s| s.protocol.starts_with("TCP") && s.port == port)
+            .map(|s| s.pid)
+            .collect();
+        pids.sort_unstable();
+        pids.dedup();
+        pids
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let pids = run_lsof(&["-t", "-i", &format!(":{}", port), "-sTCP:LISTEN"], true)?
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .collect();
+
+    Ok(pids)
+}
+
+/// Sends `signal` to each of `pids` through `send_audited_signal`, returning
+/// its message or `"PID <n> failed: ..."` per PID, then drops the listing cache.
+fn signal_each(state: &AppState, pids: Vec<u32>, signal: i32) -> Vec<String> {
+    let outcomes = pids
+        .into_iter()
+        .map(|pid| match send_audited_signal(state, pid, signal) {
+            Ok(message) => message,
+            Err(e) => format!("PID {} failed: {}", pid, e),
+        })
+        .collect();
+    state.invalidate_cache();
+    outcomes
+}
+
+/// Sends `signal` to every process listening on `port`, reporting one outcome
+/// per PID. This app's own process is left out, e.g. when `port` is the API server's.
+#[tauri::command]
+fn kill_by_port(state: State<'_, AppState>, port: u16, signal: i32) -> Result<Vec<String>, String> {
+    tracing::debug!("kill_by_port command called for port {} with signal {}", port, signal);
+
+    let mut pids = listening_pids(port)?;
+    if pids.is_empty() {
+        return Err(format!("No process is listening on port {}", port));
+    }
+    let own_pid = std::process::id();
+    pids.retain(|&pid| pid != own_pid);
+    if pids.is_empty() {
+        return Err(format!("Port {} is held by this app itself", port));
+    }
+
+    Ok(signal_each(&state, pids, signal))
+}
+
+/// Returns the PIDs whose command line matches `pattern`, via `pgrep -f`.
+///
+/// `pattern` is passed straight to `pgrep` as an argument (after `--`, so it can't
+/// be read as an option) and never goes through a shell. When `pgrep` isn't
+/// installed, falls back to a substring match over `list_ports` results.
+fn matching_pids(state: &AppState, provider: &dyn ProcessInfoProvider, pattern: &str) -> Result<Vec<u32>, String> {
+    let own_pid = std::process::id();
+
+    let output = match Command::new("pgrep").args(["-f", "--", pattern]).output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            tracing::debug!("pgrep not found, matching against list_ports results");
+
+            let mut pids: Vec<u32> = state
+                .fresh_ports(|| provider.get_listening_ports())?
+                .into_iter()
+                .filter(|p| p.process_name.contains(pattern) || p.command.contains(pattern))
+                .flat_map(|p| p.pids.into_iter().map(|pid| pid.pid))
+                .filter(|pid| *pid != own_pid)
+                .collect();
+            pids.sort_unstable();
+            pids.dedup();
+            return Ok(pids);
+        }
+        Err(e) => return Err(format!("Failed to execute pgrep: {}", e)),
+    };
+
+    // pgrep exits 1 when nothing matches and 2+ on a bad pattern
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!(
+            "pgrep failed for pattern {:?}: {}",
+            pattern,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .filter(|pid| *pid != own_pid)
+        .collect())
+}
+
+/// Sends `signal` to every process whose command line matches `pattern`,
+/// reporting one outcome per PID.
+#[tauri::command]
+fn kill_by_name(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pattern: String,
+    signal: i32,
+) -> Result<Vec<String>, String> {
+    tracing::debug!("kill_by_name command called for {:?} with signal {}", pattern, signal);
+
+    if pattern.is_empty() {
+        return Err("Pattern must not be empty".to_string());
+    }
+
+    let pids = matching_pids(&state, &**provider, &pattern)?;
+    if pids.is_empty() {
+        return Err(format!("No process matches {:?}", pattern));
+    }
+
+    Ok(signal_each(&state, pids, signal))
+}
+
+/// Sends `signal` to `root_pid` and every descendant it has according to one
+/// `ps` snapshot, children before their parents and the root last, so each
+/// child goes while its parent is still there to reap it rather than being
+/// reparented to init. Returns the PIDs that were signalled;
+/// failures (e.g. a child that exited meanwhile) are logged and skipped.
+///
+/// A visited set guards against cycles in the PPID graph, which PID reuse can
+/// produce. This app's own process is never signalled, even when it is one of
+/// the descendants (e.g. of the shell that launched it).
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn kill_tree(state: State<'_, AppState>, root_pid: u32, signal: i32) -> Result<Vec<u32>, String> {
+    tracing::debug!("kill_tree command called for PID {} with signal {}", root_pid, signal);
+
+    validate_signal(signal)?;
+    if root_pid == 0 || !is_process_alive(root_pid) {
+        return Err(format!("Process {} not found", root_pid));
+    }
+
+    // Depth-first with an explicit stack, emitting each PID after all of its
+    // descendants (post-order)
+    let children = read_process_children()?;
+    let mut visited: HashSet<u32> = HashSet::from([root_pid]);
+    let mut order = Vec::new();
+    let mut stack = vec![(root_pid, false)];
+    while let Some((pid, expanded)) = stack.pop() {
+        if expanded {
+            order.push(pid);
+            continue;
+        }
+        stack.push((pid, true));
+        for &child in children.get(&pid).into_iter().flatten() {
+            if visited.insert(child) {
+                stack.push((child, false));
+            }
+        }
+    }
+
+    let own_pid = std::process::id();
+    let signalled = order
+        .into_iter()
+        .filter(|&pid| pid != own_pid)
+        .filter(|&pid| match send_audited_signal(&state, pid, signal) {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!("kill_tree skipped PID {}: {}", pid, e);
+                false
+            }
+        })
+        .collect();
+    state.invalidate_cache();
+    Ok(signalled)
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn kill_tree(_root_pid: u32, _signal: i32) -> Result<Vec<u32>, String> {
+    Err("Process trees are not supported on Windows yet".to_string())
+}
+
+/// Sends `signal` to every process in group `pgid`, so children of a group
+/// leader (e.g. supervisord workers) aren't left behind as orphans. Refused for
+/// groups 0 and 1, the app's own group and groups with a protected member.
+#[cfg(not(target_os = "windows"))]
+#[tauri::command]
+fn kill_process_group(state: State<'_, AppState>, pgid: u32, signal: i32) -> Result<String, String> {
+    tracing::debug!("Sending signal {} to process group {}", signal, pgid);
+
+    validate_signal(signal)?;
+    let table = process_ids(false)?;
+
+    signal_group(&state, pgid, signal, &table)
+        .map(|()| format!("Signal {} sent to process group {}", signal, pgid))
+        .map_err(|e| format!("Failed to send signal {} to process group {}: {}", signal, pgid, e))
+}
+
+#[cfg(target_os = "windows")]
+#[tauri::command]
+fn kill_process_group(_pgid: u32, _signal: i32) -> Result<String, String> {
+    Err("Process groups are not supported on Windows".to_string())
+}
+
+/// Sends `signal` to every process group in session `sid`.
+///
+/// `kill -- -<sid>` alone only reaches the session leader's own group, so the
+/// session's groups are looked up with `ps` and each is signalled in turn. All
+/// of them are checked as `kill_process_group` checks one before any is
+/// signalled, so a session holding group 1 or the app's own group is refused
+/// as a whole.
+#[cfg(target_os = "linux")]
+#[tauri::command]
+fn kill_session(state: State<'_, AppState>, sid: u32, signal: i32) -> Result<String, String> {
+    tracing::debug!("Sending signal {} to session {}", signal, sid);
+
+    if sid == 0 {
+        return Err("Session ID must be nonzero".to_string());
+    }
+    validate_signal(signal)?;
+
+    let table = process_ids(true)?;
+    let mut groups: Vec<u32> = table.iter().filter(|ids| ids.sid == Some(sid)).map(|ids| ids.pgid).collect();
+    groups.sort_unstable();
+    groups.dedup();
+
+    if groups.is_empty() {
+        return Err(format!("Session {} not found", sid));
+    }
+    for &pgid in &groups {
+        check_group_signallable(&state, pgid, &table)
+            .map_err(|e| format!("Refusing to signal session {}: {}", sid, e))?;
+    }
+
+    let failures: Vec<String> = groups
+        .iter()
+        .filter_map(|&pgid| signal_group(&state, pgid, signal, &table).err().map(|e| format!("group {}: {}", pgid, e)))
+        .collect();
+
+    if failures.is_empty() {
+        Ok(format!("Signal {} sent to session {} ({} process groups)", signal, sid, groups.len()))
+    } else {
+        Err(format!("Failed to send signal {} to session {}: {}", signal, sid, failures.join("; ")))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+fn kill_session(_sid: u32, _signal: i32) -> Result<String, String> {
+    Err("Session IDs are only available on Linux".to_string())
+}
+
+/// How often `kill_process_graceful` checks whether the process has exited.
+const GRACEFUL_KILL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sends SIGTERM, waits up to `timeout_ms` for the process to exit, then falls
+/// back to SIGKILL. A `timeout_ms` of zero skips straight to `kill_process`.
+#[tauri::command]
+fn kill_process_graceful(
+    state: State<'_, AppState>,
+    provider: State<'_, DynProvider>,
+    pid: u32,
+    timeout_ms: u64,
+) -> Result<String, String> {
+    tracing::debug!("Gracefully killing PID {} with timeout {} ms", pid, timeout_ms);
+
+    if timeout_ms == 0 {
+        return Ok(kill_and_record(&state, &**provider, pid)?);
+    }
+
+    send_audited_signal(&state, pid, 15)?;
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    loop {
+        if !is_process_alive(pid) {
+            tracing::info!("Process {} exited after SIGTERM", pid);
+            state.invalidate_cache();
+            return Ok(format!("Process {} terminated with SIGTERM", pid));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        thread::sleep(GRACEFUL_KILL_POLL_INTERVAL.min(deadline - now));
+    }
+
+    tracing::info!("Process {} still alive after {} ms, escalating to SIGKILL", pid, timeout_ms);
+
+    kill_and_record(&state, &**provider, pid)?;
+    Ok(format!(
+        "Process {} did not exit within {} ms and was killed with SIGKILL",
+        pid, timeout_ms
+    ))
+}
+
+/// Event emitted with a `KillEscalatedEvent` when `kill_graceful_async` falls back to SIGKILL.
+const KILL_ESCALATED_EVENT: &str = "kill-escalated";
+
+/// Payload of `"kill-escalated"`. `message` is `kill_process`'s result or error.
+#[derive(Debug, Serialize, Clone)]
+struct KillEscalatedEvent {
+    pid: u32,
+    timeout_ms: u64,
+    success: bool,
+    message: String,
+}
+
+/// Like `kill_process_graceful`, but returns as soon as SIGTERM is sent and
+/// escalates to SIGKILL from a background thread, emitting `"kill-escalated"`
+/// if it has to. The escalation can be called off with `cancel_kill`.
+#[tauri::command]
+fn kill_graceful_async(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pid: u32,
+    timeout_ms: u64,
+) -> Result<String, String> {
+    tracing::debug!("kill_graceful_async command called for PID {} with timeout {} ms", pid, timeout_ms);
+
+    let (id, cancelled) = state.start_pending_kill(pid)?;
+    if let Err(e) = send_audited_signal(&state, pid, 15) {
+        state.finish_pending_kill(pid, id);
+        return Err(e);
+    }
+
+    thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            if !is_process_alive(pid) {
+                tracing::info!("Process {} exited after SIGTERM", pid);
+                state.invalidate_cache();
+                state.finish_pending_kill(pid, id);
+                return;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            // Only a cancel, which drops the sender, ends the wait early
+            match cancelled.recv_timeout(GRACEFUL_KILL_POLL_INTERVAL.min(deadline - now)) {
+                Err(RecvTimeoutError::Timeout) => continue,
+                _ => {
+                    tracing::info!("Escalation for PID {} cancelled", pid);
+                    return;
+                }
+            }
+        }
+
+        tracing::info!("Process {} still alive after {} ms, escalating to SIGKILL", pid, timeout_ms);
+        let result = kill_and_record(&state, &**app.state::<DynProvider>(), pid);
+        state.finish_pending_kill(pid, id);
+        let _ = app.emit(
+            KILL_ESCALATED_EVENT,
+            KillEscalatedEvent {
+                pid,
+                timeout_ms,
+                success: result.is_ok(),
+                message: result.unwrap_or_else(|e| e.to_string()),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Sent SIGTERM to process {}; SIGKILL follows in {} ms unless it exits",
+        pid, timeout_ms
+    ))
+}
+
+/// Calls off the SIGKILL escalation `kill_graceful_async` scheduled for `pid`.
+#[tauri::command]
+fn cancel_kill(state: State<'_, AppState>, pid: u32) -> Result<(), String> {
+    tracing::debug!("cancel_kill command called for PID {}", pid);
+
+    if state.cancel_pending_kill(pid) {
+        Ok(())
+    } else {
+        Err(format!("No kill is pending for PID {}", pid))
+    }
+}
+
+/// Prints `tracing` events to stderr, or appends them to the config's log file,
+/// filtered by `RUST_LOG` or else the config's level. Setting
+/// `PROCESS_MONITOR_LOG_JSON` switches to one JSON object per line, e.g. for
+/// shipping to a log file.
+#[cfg(feature = "tracing-subscriber")]
+fn init_logging(logging: &config::LoggingConfig) {
+    use std::fs::OpenOptions;
+    use std::sync::Mutex;
+    use tracing_subscriber::fmt::writer::BoxMakeWriter;
+    use tracing_subscriber::EnvFilter;
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&logging.level));
+    let file = logging.file.as_ref().map(|path| OpenOptions::new().create(true).append(true).open(path));
+    let (writer, file_error) = match file {
+        Some(Ok(file)) => (BoxMakeWriter::new(Mutex::new(file)), None),
+        Some(Err(e)) => (BoxMakeWriter::new(io::stderr), Some(e)),
+        None => (BoxMakeWriter::new(io::stderr), None),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(logging.file.is_none())
+        .with_writer(writer);
+
+    // Fails only if a subscriber is already installed, which is fine to keep
+    let _ = if std::env::var_os("PROCESS_MONITOR_LOG_JSON").is_some() {
+        builder.json().try_init()
+    } else {
+        builder.try_init()
+    };
+    if let (Some(path), Some(e)) = (&logging.file, file_error) {
+        tracing::warn!("Logging to stderr, failed to open {}: {}", path.display(), e);
+    }
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    services::preload();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![list_ports, kill_process])
+        .manage(MonitorState::default())
+        .manage(ThresholdWatchState::default())
+        .manage(PortWatchState::default())
+        .manage(ApiServerState::default())
+        .manage(provider::platform_provider())
+        .setup(|app| {
+            // Read first, since it says how to log; problems are reported once logging is up
+            let config_path = app.path().app_config_dir()?.join(config::CONFIG_FILE);
+            let (config, read_error) = match config::read(&config_path) {
+                Ok(config) => (config, None),
+                Err(e) => (Config::default(), Some(e)),
+            };
+            #[cfg(feature = "tracing-subscriber")]
+            init_logging(&config.logging);
+            if let Some(e) = read_error {
+                tracing::warn!("Using the default settings: {}", e);
+            }
+
+            let data_dir = app.path().app_data_dir()?;
+            app.manage(AppState::new(
+                state::DEFAULT_CACHE_TTL_MS,
+                state::DEFAULT_ENV_BLOCKLIST,
+                data_dir.join(state::AUDIT_LOG_FILE),
+                data_dir.join(state::WATCHLIST_FILE),
+            ));
+            let config = match config.apply(&app.state::<AppState>()) {
+                Ok(()) => config,
+                Err(e) => {
+                    tracing::warn!("Using the default settings, {} is invalid: {}", config_path.display(), e);
+                    Config::default()
+                }
+            };
+            app.manage(ConfigState::new(config_path, config));
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            list_ports,
+            list_ports_page,
+            pin_process,
+            unpin_process,
+            list_pinned,
+            search_processes,
+            get_metrics,
+            get_port_statistics,
+            top_by_cpu,
+            top_by_memory,
+            list_ports_diff,
+            take_snapshot,
+            compare_snapshots,
+            list_snapshots,
+            export_to_json,
+            export_to_csv,
+            clear_cache,
+            load_config,
+            save_config,
+            reset_config,
+            configure_paths,
+            configure_timeouts,
+            set_ps_format,
+            configure_collection_limit,
+            check_dependencies,
+            list_ports_for_user,
+            list_ports_in_range,
+            list_connections,
+            list_unix_sockets,
+            get_system_overview,
+            list_ports_with_labels,
+            lookup_port,
+            find_process_by_port,
+            list_zombie_processes,
+            kill_zombie_parent,
+            get_process_tree,
+            get_process_ancestors,
+            get_descendant_tree,
+            check_process_health,
+            list_network_interfaces,
+            get_process_detail,
+            get_process_security,
+            get_network_io_rate,
+            get_process_cwd,
+            set_oom_score_adj,
+            get_cpu_affinity,
+            set_cpu_affinity,
+            get_scheduler_info,
+            get_cgroup_stats,
+            get_open_files,
+            get_network_connections,
+            list_containers,
+            get_process_resource_limits,
+            get_process_limits,
+            get_disk_io,
+            get_memory_map,
+            get_shared_libraries,
+            get_process_env,
+            get_process_args,
+            start_monitor,
+            stop_monitor,
+            start_api_server,
+            stop_api_server,
+            watch_thresholds,
+            unwatch_thresholds,
+            watch_ports,
+            unwatch_ports,
+            get_monitor_status,
+            send_signal,
+            kill_processes,
+            kill_process,
+            kill_process_dry_run,
+            kill_process_graceful,
+            suspend_process,
+            resume_process,
+            kill_graceful_async,
+            cancel_kill,
+            kill_by_port,
+            get_kill_history,
+            get_kill_policy,
+            set_kill_policy,
+            kill_by_name,
+            kill_process_group,
+            kill_tree,
+            kill_session
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn parse_process_ids_reads_pid_pgid_and_sid() {
+        let stdout = "    1     1     1\n  812   812   812\n  813   812   812\n garbage\n";
+        let ids = parse_process_ids(stdout);
+        assert_eq!(
+            ids,
+            vec![
+                ProcessIds { pid: 1, pgid: 1, sid: Some(1) },
+                ProcessIds { pid: 812, pgid: 812, sid: Some(812) },
+                ProcessIds { pid: 813, pgid: 812, sid: Some(812) },
+            ]
+        );
+        assert_eq!(parse_process_ids("  42  40\n")[0].sid, None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn check_group_signallable_refuses_dangerous_groups() {
+        let (state, _dir) = AppState::for_tests();
+        let own_pid = std::process::id();
+        let table = [
+            ProcessIds { pid: 1, pgid: 1, sid: Some(1) },
+            ProcessIds { pid: own_pid, pgid: 4000, sid: Some(4000) },
+        ];
+
+        assert!(check_group_signallable(&state, 0, &table).is_err());
+        assert!(check_group_signallable(&state, 1, &table).is_err());
+        let own_group = check_group_signallable(&state, 4000, &table).unwrap_err();
+        assert!(own_group.contains("app belongs to"), "{}", own_group);
+        let missing = check_group_signallable(&state, 5000, &table).unwrap_err();
+        assert!(missing.contains("not found"), "{}", missing);
+    }
+
+    #[test]
+    fn send_audited_signal_refuses_pid_zero() {
+        let (state, _dir) = AppState::for_tests();
+        // Signal 0 only checks for existence, so a missing guard can't hurt the test run
+        let error = send_audited_signal(&state, 0, 0).unwrap_err();
+        assert_eq!(error, "PID must be nonzero");
+
+        let history = state.audit_log().last_entries(1).unwrap();
+        assert_eq!(history.len(), 1, "the refused attempt is still audited");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn signal_each_reports_what_was_sent() {
+        let (state, _dir) = AppState::for_tests();
+        let collections = std::cell::Cell::new(0);
+        let collect = || {
+            collections.set(collections.get() + 1);
+            Ok::<_, ListPortsError>(Vec::new())
+        };
+        state.cached_ports(collect).unwrap();
+
+        let pid = std::process::id();
+        // Signal 0 only checks that the process exists
+        let outcomes = signal_each(&state, vec![pid, 0], 0);
+        assert_eq!(outcomes[0], format!("Signal 0 sent to process {}", pid));
+        assert_eq!(outcomes[1], "PID 0 failed: PID must be nonzero");
+
+        state.cached_ports(collect).unwrap();
+        assert_eq!(collections.get(), 2, "signalling drops the cached listing");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn signal_and_record_refuses_and_audits_pid_zero() {
+        let (state, _dir) = AppState::for_tests();
+        let results = signal_and_record(&state, &provider::LsofProvider, &[0, 0], 15);
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| matches!(r, Err(KillError::ProcessNotFound(0)))));
+        assert_eq!(state.audit_log().last_entries(10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn invalidate_cache_keeps_the_diff_baseline() {
+        let (state, _dir) = AppState::for_tests();
+        let collections = std::cell::Cell::new(0);
+        let collect = || {
+            collections.set(collections.get() + 1);
+            Ok::<_, ListPortsError>(Vec::new())
+        };
+        state.cached_ports(collect).unwrap();
+        state.replace_snapshot(Vec::new());
+
+        state.invalidate_cache();
+        state.cached_ports(collect).unwrap();
+        assert_eq!(collections.get(), 2);
+        assert!(state.replace_snapshot(Vec::new()).is_some());
+
+        state.clear_cache();
+        assert!(state.replace_snapshot(Vec::new()).is_none());
+    }
+
+    fn pid_info(pid: u32, ports: Vec<u16>, details: ProcessDetails) -> PidInfo {
+        PidInfo::from_details(pid, "TCP".to_string(), "LISTEN".to_string(), ports, "*".to_string(), details)
+    }
+
+    fn port_info(pids: Vec<PidInfo>) -> PortInfo {
+        PortInfo {
+            process_name: "nginx".to_string(),
+            command: "nginx: master process".to_string(),
+            pids,
+            connection_count: 0,
+        }
+    }
+
+    /// A group named `name` whose one PID, listening on 8000, uses `cpu` percent.
+    fn cpu_group(name: &str, pid: u32, cpu: f32) -> PortInfo {
+        let details = ProcessDetails {
+            cpu: Some(cpu),
+            ..ProcessDetails::default()
+        };
+        PortInfo {
+            process_name: name.to_string(),
+            ..port_info(vec![pid_info(pid, vec![8000], details)])
+        }
+    }
+
+    #[test]
+    fn ports_diff_ignores_growing_counters() {
+        let details = |uptime_seconds, cpu_time_secs, net_rx_bytes| ProcessDetails {
+            user: "www-data".to_string(),
+            uptime_seconds,
+            cpu_time_secs,
+            net_rx_bytes,
+            ..ProcessDetails::default()
+        };
+        let previous = [port_info(vec![pid_info(100, vec![80], details(10, 1, 500))])];
+        let current = [port_info(vec![pid_info(100, vec![80], details(12, 2, 900))])];
+        let diff = PortsDiff::between(&previous, &current);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+
+        let rebound = [port_info(vec![pid_info(100, vec![80, 443], details(12, 2, 900))])];
+        assert_eq!(PortsDiff::between(&previous, &rebound).changed, rebound);
+    }
+
+    #[test]
+    fn pid_info_serializes_to_the_frontend_shape() {
+        let details = ProcessDetails {
+            user: "www-data".to_string(),
+            uid: Some(33),
+            cpu: Some(2.5),
+            mem: None,
+            rss_kb: 10_240,
+            ppid: 1,
+            ..ProcessDetails::default()
+        };
+        let json = serde_json::to_value(pid_info(4242, vec![80, 443], details)).unwrap();
+        // Names come from the host's /etc/services, so take whatever it calls these ports
+        let service_names: Vec<String> = [80, 443].into_iter().filter_map(services::port_to_service_name).collect();
+
+        // Every key of the PidInfo interface in +page.svelte, so neither side can drift alone
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "pid": 4242,
+                "protocol": "TCP",
+                "protocols": [],
+                "state": "LISTEN",
+                "ports": [80, 443],
+                "bind_address": "*",
+                "port_labels": [],
+                "service_names": service_names,
+                "is_ephemeral": false,
+                "user": "www-data",
+                "uid": 33,
+                "is_root": false,
+                "net_rx_bytes": 0,
+                "net_tx_bytes": 0,
+                "cpu": 2.5,
+                "cpu_time": "",
+                "cpu_time_secs": 0,
+                "mem": null,
+                "vsz_kb": 0,
+                "rss_kb": 10_240,
+                "start_time": "",
+                "uptime_seconds": 0,
+                "process_state": "",
+                "ppid": 1,
+                "pgid": 0,
+                "sid": 0,
+                "nice": 0,
+                "threads": 0,
+                "cwd": "",
+                "fd_count": 0,
+                "exe_path": "",
+                "arch": null,
+                "oom_score": null,
+                "oom_score_adj": null,
+                "container_id": null,
+                "systemd_unit": null,
+                "cgroup_path": null,
+                "launchd_label": null,
+                "is_signed": null,
+                "signing_authority": null,
+                "ps_extra": {},
+            })
+        );
+    }
+
+    #[test]
+    fn killable_zombie_parent_spares_init_and_the_app() {
+        let zombie = |ppid| ZombieInfo {
+            pid: 5120,
+            process_name: "worker".to_string(),
+            ppid,
+            user: "alice".to_string(),
+        };
+
+        assert_eq!(killable_zombie_parent(&zombie(5100)), Ok(5100));
+        assert!(killable_zombie_parent(&zombie(1)).unwrap_err().contains("init"));
+        assert!(killable_zombie_parent(&zombie(std::process::id())).unwrap_err().contains("this app"));
+    }
+
+    /// Well above any PID the OS hands out, so the kill policy finds no name or owner.
+    const FAKE_PID: u32 = 4_000_000_001;
+
+    #[test]
+    fn sorted_ports_puts_pinned_groups_first() {
+        let (state, _dir) = AppState::for_tests();
+        let provider = provider::MockProvider {
+            ports: vec![cpu_group("low", 1001, 1.0), cpu_group("high", 1002, 5.0), cpu_group("pinned", 1003, 3.0)],
+            ..provider::MockProvider::default()
+        };
+        state.watchlist().pin(1003).unwrap();
+
+        let ports = sorted_ports(&state, &provider, Some(SortField::Cpu)).unwrap().ports;
+        let names: Vec<&str> = ports.iter().map(|p| p.process_name.as_str()).collect();
+        assert_eq!(names, ["pinned", "high", "low"]);
+    }
+
+    #[test]
+    fn kill_and_record_checks_then_kills() {
+        let (state, _dir) = AppState::for_tests();
+        let provider = provider::MockProvider {
+            alive: vec![FAKE_PID],
+            ..provider::MockProvider::default()
+        };
+
+        let message = kill_and_record(&state, &provider, FAKE_PID).unwrap();
+        assert_eq!(message, format!("Process {} killed successfully", FAKE_PID));
+        assert_eq!(*provider.signals.lock().unwrap(), [(FAKE_PID, 0), (FAKE_PID, 9)]);
+
+        let gone = FAKE_PID + 1;
+        let result = kill_and_record(&state, &provider, gone);
+        assert!(matches!(result, Err(KillError::ProcessNotFound(pid)) if pid == gone));
+        assert_eq!(provider.signals.lock().unwrap().last(), Some(&(gone, 0)));
+        assert_eq!(state.audit_log().last_entries(10).unwrap().len(), 2);
+    }
+
+    /// Fails the test if anything reaches the provider.
+    struct UnreachableProvider;
+
+    impl ProcessInfoProvider for UnreachableProvider {
+        fn get_listening_ports(&self) -> Result<Vec<PortInfo>, ListPortsError> {
+            panic!("the provider should not be asked for a listing");
+        }
+
+        fn kill_pid(&self, pid: u32, signal: i32) -> Result<(), KillError> {
+            panic!("the provider should not be asked to send signal {} to {}", signal, pid);
+        }
+    }
+
+    #[test]
+    fn pid_zero_never_reaches_the_provider() {
+        assert!(matches!(check_signallable(&UnreachableProvider, 0), Err(KillError::ProcessNotFound(0))));
+
+        let (state, _dir) = AppState::for_tests();
+        let result = kill_and_record(&state, &UnreachableProvider, 0);
+        assert!(matches!(result, Err(KillError::ProcessNotFound(0))));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn collect_process_details_reads_own_memory() {
+        let pid = std::process::id();
+        let details = collect_process_details([pid]);
+
+        let own = details.get(&pid).expect("ps should report the test process");
+        assert!(own.vsz_kb > 0);
+        assert!(own.rss_kb > 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn only_get_process_detail_reads_the_working_directory() {
+        let pid = std::process::id();
+        let listed = collect_process_details([pid]).remove(&pid).unwrap();
+        assert!(listed.cwd.is_empty());
+        assert!(listed.exe_path.is_empty());
+
+        let detail = get_process_detail(pid).unwrap();
+        assert_eq!(std::path::Path::new(&detail.cwd), std::env::current_dir().unwrap());
+        assert!(!detail.exe_path.is_empty());
+        assert!(detail.fd_count > 0);
+    }
+
+    #[test]
+    fn sort_ports_by_memory_uses_resident_size() {
+        let group = |name: &str, mem, rss_kb| {
+            let details = ProcessDetails {
+                mem: Some(mem),
+                rss_kb,
+                ..ProcessDetails::default()
+            };
+            PortInfo {
+                process_name: name.to_string(),
+                ..port_info(vec![pid_info(1, vec![8000], details)])
+            }
+        };
+        let mut ports = vec![group("small", 9.0, 1_000), group("large", 0.1, 500_000)];
+
+        sort_ports(&mut ports, SortField::Memory);
+        assert_eq!(ports[0].process_name, "large");
+    }
+
+    #[test]
+    fn process_limits_convert_units_and_unlimited() {
+        let limits = ResourceLimits {
+            max_open_files: Some(1024),
+            max_open_files_hard: None,
+            max_stack_size: Some(8 * 1024 * 1024),
+            max_virtual_memory: None,
+            max_processes: Some(63_448),
+            max_processes_hard: Some(63_448),
+            open_files: Some(950),
+            open_files_near_limit: true,
+            ..ResourceLimits::default()
+        };
+
+        let expected = ProcessLimits {
+            max_open_files_soft: 1024,
+            max_open_files_hard: u64::MAX,
+            current_open_files: 950,
+            max_processes_soft: 63_448,
+            max_processes_hard: 63_448,
+            max_stack_size_kb: 8192,
+            max_virtual_mem_kb: None,
+            open_files_near_limit: true,
+        };
+        assert_eq!(ProcessLimits::from(limits), expected);
+    }
+
+    #[test]
+    fn collections_over_the_limit_report_their_request_id() {
+        let (state, _dir) = AppState::for_tests();
+        let collect = || Ok::<_, ListPortsError>(Vec::new());
+
+        state.configure_collection_limit(0, false);
+        let refused = match state.fresh_ports(collect) {
+            Err(ListPortsError::Busy { request_id }) => request_id,
+            other => panic!("expected Busy, got {:?}", other),
+        };
+
+        state.configure_collection_limit(1, false);
+        let (request_id, _) = state.tracked_ports(collect).unwrap();
+        assert_eq!(request_id, refused + 1);
+    }
+
+    #[test]
+    fn top_ports_reads_the_cached_listing() {
+        let (state, _dir) = AppState::for_tests();
+        let provider = provider::MockProvider {
+            ports: vec![cpu_group("idle", 1001, 0.5), cpu_group("busy", 1002, 80.0)],
+            ..provider::MockProvider::default()
+        };
+
+        let top = top_ports(&state, &provider, 1, SortField::Cpu).unwrap();
+        assert_eq!(top[0].process_name, "busy");
+        assert_eq!(top_ports(&state, &UnreachableProvider, 2, SortField::Cpu).unwrap().len(), 2);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn parse_elapsed_reads_every_etime_shape() {
+        assert_eq!(parse_elapsed("00:07"), Some(7));
+        assert_eq!(parse_elapsed("12:34"), Some(754));
+        assert_eq!(parse_elapsed("01:05:12"), Some(3_912));
+        assert_eq!(parse_elapsed("3-01:05:12"), Some(3 * 86_400 + 3_912));
+        assert_eq!(parse_elapsed(""), None);
+        assert_eq!(parse_elapsed("1:xx"), None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn parse_cpu_time_reads_linux_and_macos_formats() {
+        // Linux `ps -o time=`
+        assert_eq!(parse_cpu_time("00:00:03"), Some(3));
+        assert_eq!(parse_cpu_time("2-03:04:05"), Some(2 * 86_400 + 3 * 3_600 + 4 * 60 + 5));
+        // macOS, with the fraction dropped
+        assert_eq!(parse_cpu_time("12:34.56"), Some(754));
+        assert_eq!(parse_cpu_time("0:00.01"), Some(0));
+        // Never scheduled
+        assert_eq!(parse_cpu_time("-"), None);
+    }
+
+    #[test]
+    fn snapshot_diff_matches_pids_across_groups() {
+        let worker = |pid, rss_kb| {
+            let details = ProcessDetails {
+                rss_kb,
+                ..ProcessDetails::default()
+            };
+            pid_info(pid, vec![80], details)
+        };
+        let before = [port_info(vec![worker(100, 4_096), worker(101, 4_096)])];
+        let after = [port_info(vec![worker(101, 8_192), worker(102, 4_096)])];
+
+        let diff = SnapshotDiff::between(&before, &after);
+        assert_eq!(diff.new_processes, [port_info(vec![worker(102, 4_096)])]);
+        assert_eq!(diff.gone_processes, [port_info(vec![worker(100, 4_096)])]);
+        assert_eq!(diff.changed_pids, [(worker(101, 4_096), worker(101, 8_192))]);
+
+        assert!(SnapshotDiff::between(&after, &after).changed_pids.is_empty());
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+    fn socket_summary(sockets: &[SocketEntry]) -> Vec<(&str, u32, &str, &str, u16, &str)> {
+        sockets
+            .iter()
+            .map(|s| {
+                let (protocol, bind_address) = (s.protocol.as_str(), s.bind_address.as_str());
+                (s.process_name.as_str(), s.pid, protocol, bind_address, s.port, s.state.as_str())
+            })
+            .collect()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+    #[test]
+    fn parse_lsof_socket_fields_reads_field_output() {
+        let stdout = "\
+p1201
+cnginx
+f6
+tIPv4
+PTCP
+n*:80
+TST=LISTEN
+TQR=0
+TQS=0
+f7
+tIPv6
+PTCP
+n[::]:80
+TST=LISTEN
+TQR=0
+TQS=0
+p2044
+cGoogle Chrome Helper
+f19
+tIPv4
+PTCP
+n127.0.0.1:3000->127.0.0.1:51234
+TST=ESTABLISHED
+f21
+tIPv6
+PUDP
+n[fe80::1]:5353
+";
+        assert_eq!(
+            socket_summary(&parse_lsof_socket_fields(stdout)),
+            [
+                ("nginx", 1201, "TCP", "*", 80, "LISTEN"),
+                ("nginx", 1201, "TCP6", "::", 80, "LISTEN"),
+                ("Google Chrome Helper", 2044, "TCP", "127.0.0.1", 3000, "ESTABLISHED"),
+                ("Google Chrome Helper", 2044, "UDP6", "fe80::1", 5353, ""),
+            ]
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn parse_lsof_connections_keeps_both_ends() {
+        let stdout = "\
+p2044
+cnode
+f19
+tIPv4
+PTCP
+n127.0.0.1:3000->127.0.0.1:51234
+TST=ESTABLISHED
+TQR=0
+TQS=0
+f20
+tIPv4
+PTCP
+n*:3000
+TST=LISTEN
+f23
+tIPv6
+PTCP
+n[2001:db8::5]:52814->[2606:4700::6810:85e5]:443
+TST=CLOSE_WAIT
+f24
+tIPv6
+PUDP
+n[::1]:5353
+";
+        let connections: Vec<_> = parse_lsof_connections(stdout)
+            .into_iter()
+            .map(|c| (c.local_addr, c.remote_addr, c.protocol, c.state))
+            .collect();
+        let expected = [
+            ("127.0.0.1:3000", "127.0.0.1:51234", "TCP", "ESTABLISHED"),
+            ("*:3000", "*", "TCP", "LISTEN"),
+            ("[2001:db8::5]:52814", "[2606:4700::6810:85e5]:443", "TCP6", "CLOSE_WAIT"),
+            ("[::1]:5353", "*", "UDP6", ""),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|&(local, remote, protocol, state)| {
+                (local.to_string(), remote.to_string(), protocol.to_string(), state.to_string())
+            })
+            .collect();
+        assert_eq!(connections, expected);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn parse_lsof_unix_sockets_reads_linux_and_macos_names() {
+        // Linux, then macOS, which names no type and gives unnamed sockets by address
+        let stdout = "\
+p812
+csystemd
+Lroot
+f17
+n/run/systemd/notify type=DGRAM
+f23
+ntype=STREAM
+p1502
+cdbus-daemon
+Lmessagebus
+f4
+n@/tmp/dbus-Wd3kXmQ2 type=SEQPACKET
+p431
+credis-server
+Lalice
+f6
+n/tmp/redis.sock
+f9
+n->0xf1e2d3c4b5a69788
+";
+        let sockets = parse_lsof_unix_sockets(stdout);
+        let sockets: Vec<_> = sockets
+            .iter()
+            .map(|s| (s.process_name.as_str(), s.pid, s.user.as_str(), s.socket_path.as_str(), s.socket_type.as_str()))
+            .collect();
+        assert_eq!(
+            sockets,
+            [
+                ("systemd", 812, "root", "/run/systemd/notify", "DGRAM"),
+                ("systemd", 812, "root", "(anonymous)", "STREAM"),
+                ("dbus-daemon", 1502, "messagebus", "@/tmp/dbus-Wd3kXmQ2", "SEQPACKET"),
+                ("redis-server", 431, "alice", "/tmp/redis.sock", ""),
+                ("redis-server", 431, "alice", "(anonymous)", ""),
+            ]
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn parse_lsof_open_files_reads_each_descriptor() {
+        // No s line for the FIFO or the socket: lsof only prints fields it knows
+        let stdout = "\
+p1201
+fcwd
+tDIR
+D0xfd01
+s4096
+i2
+n/
+ftxt
+tREG
+D0xfd01
+s1234568
+i1048712
+n/usr/sbin/nginx
+f0
+tCHR
+D0x5
+i6
+n/dev/null
+f5
+tFIFO
+D0xc
+i34871
+npipe
+f7
+tunix
+D0xffff8f5a2c3e4400
+i45112
+n/run/nginx.sock type=STREAM
+";
+        let files: Vec<_> = parse_lsof_open_files(stdout)
+            .into_iter()
+            .map(|f| (f.fd, f.type_, f.device, f.size, f.node, f.name))
+            .collect();
+        let expected = [
+            ("cwd", "DIR", "0xfd01", 4096, "2", "/"),
+            ("txt", "REG", "0xfd01", 1_234_568, "1048712", "/usr/sbin/nginx"),
+            ("0", "CHR", "0x5", 0, "6", "/dev/null"),
+            ("5", "FIFO", "0xc", 0, "34871", "pipe"),
+            ("7", "unix", "0xffff8f5a2c3e4400", 0, "45112", "/run/nginx.sock type=STREAM"),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|&(fd, type_, device, size, node, name)| {
+                (fd.to_string(), type_.to_string(), device.to_string(), size, node.to_string(), name.to_string())
+            })
+            .collect();
+        assert_eq!(files, expected);
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "freebsd")))]
+    #[test]
+    fn parse_lsof_sockets_reads_columnar_output() {
+        let stdout = "\
+COMMAND    PID   USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+nginx     1201   root    6u  IPv4  23456      0t0  TCP *:80 (LISTEN)
+nginx     1201   root    7u  IPv6  23457      0t0  TCP [::]:80 (LISTEN)
+node      2044  alice   19u  IPv4  34567      0t0  TCP 127.0.0.1:3000->127.0.0.1:51234 (ESTABLISHED)
+avahi-dae  812  avahi   12u  IPv4  17890      0t0  UDP *:5353
+";
+        assert_eq!(
+            socket_summary(&parse_lsof_sockets(stdout)),
+            [
+                ("nginx", 1201, "TCP", "*", 80, "LISTEN"),
+                ("nginx", 1201, "TCP6", "::", 80, "LISTEN"),
+                ("node", 2044, "TCP", "127.0.0.1", 3000, "ESTABLISHED"),
+                ("avahi-dae", 812, "UDP", "*", 5353, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn port_statistics_count_shared_bindings_once() {
+        let listener = |pid, user: &str, protocol: &str, bind_address: &str, ports| {
+            let details = ProcessDetails {
+                user: user.to_string(),
+                ..ProcessDetails::default()
+            };
+            let (protocol, state) = (protocol.to_string(), "LISTEN".to_string());
+            PidInfo::from_details(pid, protocol, state, ports, bind_address.to_string(), details)
+        };
+        let ports = [
+            // A prefork master and worker share the same sockets
+            port_info(vec![
+                listener(100, "root", "TCP", "*", vec![80, 443]),
+                listener(101, "www-data", "TCP", "*", vec![80, 443]),
+            ]),
+            port_info(vec![listener(200, "postgres", "TCP6", "::1", vec![5432])]),
+            port_info(vec![listener(300, "avahi", "UDP", "*", vec![5353])]),
+        ];
+
+        let stats = PortStatistics::from_ports(&ports);
+        assert_eq!(stats.total_listening_ports, 4);
+        assert_eq!((stats.tcp4_count, stats.tcp6_count, stats.udp_count), (2, 1, 1));
+        assert_eq!(stats.by_user["root"], 2);
+        assert_eq!(stats.by_user["www-data"], 2);
+        assert_eq!(stats.by_user["postgres"], 1);
+        assert_eq!(stats.by_protocol["TCP"], 2);
+        // Two well-known against two registered: ties go to the lower range
+        assert_eq!(stats.most_used_port_range, "well-known");
+
+        assert_eq!(PortStatistics::from_ports(&[]).most_used_port_range, "");
+    }
+
+    #[test]
+    fn kill_policy_matches_names_and_owners() {
+        let policy = KillPolicy {
+            protected_names: vec!["systemd".to_string()],
+            protected_users: vec!["alice".to_string()],
+        };
+
+        assert_eq!(policy.violation(Some("systemd"), None).unwrap(), "systemd is a protected process");
+        assert_eq!(
+            policy.violation(Some("vim"), Some("DESKTOP-1\\alice")).unwrap(),
+            "processes owned by DESKTOP-1\\alice are protected"
+        );
+        assert!(policy.violation(Some("systemd-journald"), Some("root")).is_none());
+        // Unreadable names and owners match nothing
+        assert!(policy.violation(None, None).is_none());
+
+        let blank = KillPolicy {
+            protected_names: vec![" ".to_string()],
+            protected_users: Vec::new(),
+        };
+        assert!(blank.validate().is_err());
+        assert!(KillPolicy::default().validate().is_ok());
+    }
+
+    #[test]
+    fn cached_port_merges_every_pid_on_the_port() {
+        let (state, _dir) = AppState::for_tests();
+        let envoy = PortInfo {
+            process_name: "envoy".to_string(),
+            ..port_info(vec![pid_info(200, vec![80], ProcessDetails::default())])
+        };
+        let nginx = port_info(vec![pid_info(100, vec![80, 443], ProcessDetails::default())]);
+        let listing = || Ok::<_, ListPortsError>(vec![nginx.clone(), envoy.clone()]);
+
+        let on_80 = state.cached_port(80, listing).unwrap().unwrap();
+        assert_eq!(on_80.process_name, "nginx");
+        assert_eq!(on_80.pids.iter().map(|p| p.pid).collect::<Vec<_>>(), [100, 200]);
+
+        let unreachable = || -> Result<Vec<PortInfo>, ListPortsError> { panic!("the listing should be cached") };
+        assert_eq!(state.cached_port(443, unreachable).unwrap().unwrap().pids.len(), 1);
+        assert!(state.cached_port(9999, unreachable).unwrap().is_none());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn walk_descendants_visits_each_pid_once() {
+        // 1 -> 2 -> 4, 1 -> 3, and a stale link from 4 back to 1 left by PID reuse
+        let children = HashMap::from([(1, vec![2, 3]), (2, vec![4]), (4, vec![1])]);
+
+        let (order, tree) = walk_descendants(&children, 1);
+        assert_eq!(order, [1, 2, 3, 4]);
+        assert_eq!(tree, HashMap::from([(1, vec![2, 3]), (2, vec![4])]));
+
+        let (order, tree) = walk_descendants(&children, 3);
+        assert_eq!(order, [3]);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn probe_address_maps_wildcards_to_loopback() {
+        let socket = |protocol: &str, bind_address: &str| SocketEntry {
+            process_name: "nginx".to_string(),
+            pid: 4242,
+            protocol: protocol.to_string(),
+            port: 8080,
+            bind_address: bind_address.to_string(),
+            state: "LISTEN".to_string(),
+        };
+
+        assert_eq!(probe_address(&socket("TCP", "*")), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(probe_address(&socket("TCP6", "*")), IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(probe_address(&socket("TCP", "0.0.0.0")), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(probe_address(&socket("TCP6", "::")), IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(probe_address(&socket("TCP", "192.168.1.20")), "192.168.1.20".parse::<IpAddr>().unwrap());
+    }
+}
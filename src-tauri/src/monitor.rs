@@ -0,0 +1,364 @@
+//! Background polling of the `list_ports` data that pushes changes to the
+//! frontend as `"process-update"` events instead of having it poll on a timer,
+//! and the threshold and port watchers built on the same polling loop. The port
+//! watcher can back off while nothing changes.
+
+use super::{AppState, DynProvider, PortInfo, PortsDiff};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event emitted with the `PortInfo` entries that changed since the previous poll.
+pub(crate) const PROCESS_UPDATE_EVENT: &str = "process-update";
+
+/// Event emitted with a `ThresholdEvent` when a process goes over a watched threshold.
+pub(crate) const THRESHOLD_EXCEEDED_EVENT: &str = "threshold-exceeded";
+
+/// Events emitted with a `PortChangeEvent` when a port binding appears or disappears.
+pub(crate) const PORT_OPENED_EVENT: &str = "port-opened";
+pub(crate) const PORT_CLOSED_EVENT: &str = "port-closed";
+
+/// Minimum time between two `"threshold-exceeded"` events for the same PID.
+const THRESHOLD_DEBOUNCE: Duration = Duration::from_secs(10);
+
+/// Managed state holding the running monitor, if any.
+#[derive(Default)]
+pub(crate) struct MonitorState {
+    worker: Mutex<Option<Worker>>,
+}
+
+/// Managed state holding the running threshold watcher, if any.
+#[derive(Default)]
+pub(crate) struct ThresholdWatchState {
+    worker: Mutex<Option<Worker>>,
+}
+
+/// Managed state holding the running port watcher, if any.
+#[derive(Default)]
+pub(crate) struct PortWatchState {
+    worker: Mutex<Option<Worker>>,
+}
+
+/// Payload of `"port-opened"` and `"port-closed"`.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PortChangeEvent {
+    process_name: String,
+    pid: u32,
+    port: u16,
+    protocol: String,
+}
+
+/// Payload of `"threshold-exceeded"`. `threshold_type` is `"cpu"`, `"memory"` or
+/// `"both"`; unavailable readings are reported as 0.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ThresholdEvent {
+    pid: u32,
+    process_name: String,
+    cpu: f32,
+    mem: f32,
+    threshold_type: String,
+}
+
+/// What `get_monitor_status` reports about the port watcher.
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct MonitorStatus {
+    running: bool,
+    min_interval_ms: u64,
+    max_interval_ms: u64,
+    /// How long the watcher waits before its next poll.
+    current_interval_ms: u64,
+    /// None until the first poll has finished.
+    last_poll_changed: Option<bool>,
+    /// Polls in a row that found no change, failed polls included.
+    unchanged_polls: u32,
+    polls: u64,
+}
+
+/// How long a worker waits between polls: `min` at first and after any poll
+/// that saw a change, doubling after each one that didn't, up to `max`. Equal
+/// bounds give a fixed interval.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Backoff {
+    min: Duration,
+    max: Duration,
+}
+
+impl Backoff {
+    pub(crate) fn fixed(interval: Duration) -> Self {
+        Backoff {
+            min: interval,
+            max: interval,
+        }
+    }
+
+    /// `max` is raised to `min` if it is lower.
+    pub(crate) fn between(min: Duration, max: Duration) -> Self {
+        Backoff { min, max: max.max(min) }
+    }
+
+    fn next(&self, current: Duration, changed: bool) -> Duration {
+        if changed {
+            self.min
+        } else {
+            current.saturating_mul(2).min(self.max)
+        }
+    }
+}
+
+struct Worker {
+    /// Dropping or sending on this wakes the worker and makes it exit.
+    stop: Sender<()>,
+    handle: JoinHandle<()>,
+    status: Arc<Mutex<MonitorStatus>>,
+}
+
+impl MonitorState {
+    /// Starts polling every `interval`, replacing any monitor that is already running.
+    pub(crate) fn start(&self, app: AppHandle, interval: Duration) {
+        let mut previous: Vec<PortInfo> = Vec::new();
+        let worker = Worker::spawn(app.clone(), Backoff::fixed(interval), move |current| {
+            let changes = changed_entries(&previous, &current);
+            let changed = !changes.is_empty();
+            if changed {
+                tracing::debug!("Monitor emitting {} changed process groups", changes.len());
+                let _ = app.emit(PROCESS_UPDATE_EVENT, changes);
+            }
+            previous = current;
+            changed
+        });
+        replace_worker(&self.worker, Some(worker));
+    }
+
+    /// Stops the running monitor. Returns false if none was running.
+    pub(crate) fn stop(&self) -> bool {
+        replace_worker(&self.worker, None)
+    }
+}
+
+impl ThresholdWatchState {
+    /// Starts checking every `interval` for PIDs above `cpu_percent` or `mem_percent`,
+    /// replacing any watcher that is already running.
+    pub(crate) fn start(&self, app: AppHandle, cpu_percent: f32, mem_percent: f32, interval: Duration) {
+        let mut last_emitted: HashMap<u32, Instant> = HashMap::new();
+        let worker = Worker::spawn(app.clone(), Backoff::fixed(interval), move |current| {
+            last_emitted.retain(|_, at| at.elapsed() < THRESHOLD_DEBOUNCE);
+
+            for port_info in &current {
+                for pid_info in &port_info.pids {
+                    let cpu = pid_info.cpu.unwrap_or(0.0);
+                    let mem = pid_info.mem.unwrap_or(0.0);
+                    let threshold_type = match (cpu > cpu_percent, mem > mem_percent) {
+                        (true, true) => "both",
+                        (true, false) => "cpu",
+                        (false, true) => "memory",
+                        (false, false) => continue,
+                    };
+                    // A PID with sockets in several groups is still reported once
+                    if last_emitted.contains_key(&pid_info.pid) {
+                        continue;
+                    }
+                    last_emitted.insert(pid_info.pid, Instant::now());
+
+                    tracing::info!("PID {} exceeded {} threshold", pid_info.pid, threshold_type);
+                    let _ = app.emit(
+                        THRESHOLD_EXCEEDED_EVENT,
+                        ThresholdEvent {
+                            pid: pid_info.pid,
+                            process_name: port_info.process_name.clone(),
+                            cpu,
+                            mem,
+                            threshold_type: threshold_type.to_string(),
+                        },
+                    );
+                }
+            }
+            false
+        });
+        replace_worker(&self.worker, Some(worker));
+    }
+
+    /// Stops the running watcher. Returns false if none was running.
+    pub(crate) fn stop(&self) -> bool {
+        replace_worker(&self.worker, None)
+    }
+}
+
+impl PortWatchState {
+    /// Starts polling as `backoff` says and emitting an event for each (pid, port,
+    /// protocol) binding that appeared or disappeared since the previous poll.
+    /// The first poll only records the bindings that already exist.
+    pub(crate) fn start(&self, app: AppHandle, backoff: Backoff) {
+        let mut known: Option<HashSet<PortChangeEvent>> = None;
+        let worker = Worker::spawn(app.clone(), backoff, move |current| {
+            let bindings = port_bindings(&current);
+            let mut changed = false;
+            if let Some(previous) = known.replace(bindings.clone()) {
+                for (event, binding) in bindings
+                    .difference(&previous)
+                    .map(|b| (PORT_OPENED_EVENT, b))
+                    .chain(previous.difference(&bindings).map(|b| (PORT_CLOSED_EVENT, b)))
+                {
+                    tracing::debug!("{} {} for PID {}", event, binding.port, binding.pid);
+                    let _ = app.emit(event, binding.clone());
+                    changed = true;
+                }
+            }
+            changed
+        });
+        replace_worker(&self.worker, Some(worker));
+    }
+
+    /// Stops the running watcher. Returns false if none was running.
+    pub(crate) fn stop(&self) -> bool {
+        replace_worker(&self.worker, None)
+    }
+
+    /// The running watcher's interval and recent polls, or a status with
+    /// `running` false if none is running.
+    pub(crate) fn status(&self) -> MonitorStatus {
+        let worker = self.worker.lock().unwrap_or_else(|e| e.into_inner());
+        worker
+            .as_ref()
+            .map(|worker| worker.status.lock().unwrap_or_else(|e| e.into_inner()).clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Flattens a listing into one entry per (pid, port, protocol) binding.
+fn port_bindings(ports: &[PortInfo]) -> HashSet<PortChangeEvent> {
+    ports
+        .iter()
+        .flat_map(|port_info| {
+            port_info.pids.iter().flat_map(move |pid_info| {
+                pid_info.ports.iter().map(move |&port| PortChangeEvent {
+                    process_name: port_info.process_name.clone(),
+                    pid: pid_info.pid,
+                    port,
+                    protocol: pid_info.protocol.clone(),
+                })
+            })
+        })
+        .collect()
+}
+
+/// Swaps in `next`, shutting down the worker it replaces. Returns whether one was running.
+fn replace_worker(slot: &Mutex<Option<Worker>>, next: Option<Worker>) -> bool {
+    // Shut down after unlocking: joining waits out a poll in progress, which
+    // would otherwise block status reads and other starts and stops meanwhile
+    let previous = std::mem::replace(&mut *slot.lock().unwrap_or_else(|e| e.into_inner()), next);
+    match previous {
+        Some(previous) => {
+            previous.shutdown();
+            true
+        }
+        None => false,
+    }
+}
+
+impl Worker {
+    /// Spawns a thread that hands a fresh listing from the managed `DynProvider`,
+    /// collected under the `AppState` collection limit, to `on_poll` until shut
+    /// down, waiting between polls as `backoff` says.
+    /// `on_poll` returns whether the listing changed. Failed polls are skipped
+    /// and count as unchanged.
+    fn spawn(
+        app: AppHandle,
+        backoff: Backoff,
+        mut on_poll: impl FnMut(Vec<PortInfo>) -> bool + Send + 'static,
+    ) -> Self {
+        let millis = |duration: Duration| duration.as_millis().try_into().unwrap_or(u64::MAX);
+        let status = Arc::new(Mutex::new(MonitorStatus {
+            running: true,
+            min_interval_ms: millis(backoff.min),
+            max_interval_ms: millis(backoff.max),
+            current_interval_ms: millis(backoff.min),
+            ..MonitorStatus::default()
+        }));
+
+        let (stop, stop_rx) = mpsc::channel();
+        let handle = {
+            let status = Arc::clone(&status);
+            let mut interval = backoff.min;
+            thread::spawn(move || loop {
+                let listing = app.state::<AppState>().fresh_ports(|| app.state::<DynProvider>().get_listening_ports());
+                let changed = match listing {
+                    Ok(current) => on_poll(current),
+                    Err(e) => {
+                        tracing::warn!("Monitor poll failed: {}", e);
+                        false
+                    }
+                };
+
+                let next = backoff.next(interval, changed);
+                if next != interval {
+                    tracing::debug!("Poll interval now {} ms", millis(next));
+                }
+                interval = next;
+                {
+                    let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+                    status.polls += 1;
+                    status.last_poll_changed = Some(changed);
+                    status.unchanged_polls = if changed { 0 } else { status.unchanged_polls.saturating_add(1) };
+                    status.current_interval_ms = millis(interval);
+                }
+
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            })
+        };
+
+        Worker { stop, handle, status }
+    }
+
+    fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.handle.join();
+    }
+}
+
+/// Returns the groups in `current` that are new or differ from `previous`, keyed
+/// by (process_name, command). Groups that disappeared are included with an
+/// empty `pids` list so the frontend can drop them.
+fn changed_entries(previous: &[PortInfo], current: &[PortInfo]) -> Vec<PortInfo> {
+    let diff = PortsDiff::between(previous, current);
+
+    let mut changes = diff.added;
+    changes.extend(diff.changed);
+    changes.extend(diff.removed.into_iter().map(|p| PortInfo {
+        pids: Vec::new(),
+        ..p
+    }));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_while_unchanged_and_resets_on_change() {
+        let backoff = Backoff::between(Duration::from_secs(1), Duration::from_secs(5));
+        let secs = Duration::from_secs;
+
+        assert_eq!(backoff.next(secs(1), false), secs(2));
+        assert_eq!(backoff.next(secs(2), false), secs(4));
+        assert_eq!(backoff.next(secs(4), false), secs(5));
+        assert_eq!(backoff.next(secs(5), false), secs(5));
+        assert_eq!(backoff.next(secs(5), true), secs(1));
+    }
+
+    #[test]
+    fn backoff_bounds_stay_ordered() {
+        let fixed = Backoff::fixed(Duration::from_secs(2));
+        assert_eq!(fixed.next(Duration::from_secs(2), false), Duration::from_secs(2));
+
+        let inverted = Backoff::between(Duration::from_secs(3), Duration::from_secs(1));
+        assert_eq!(inverted.next(Duration::from_secs(3), false), Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,129 @@
+//! Stand-in for `ps` on Linux systems that don't ship it (e.g. minimal container
+//! images), reading the same figures straight from `/proc`.
+
+use super::ProcessDetails;
+use std::fs;
+
+/// Clock ticks per second that `/proc/<pid>/stat` times are counted in. `USER_HZ`
+/// is 100 on every mainstream Linux ABI, and reading it needs `sysconf`.
+const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+/// Fills the fields `ps` would have from `/proc/<pid>/stat`, `/proc/<pid>/status`
+/// and `/proc/<pid>/cmdline`. `%cpu` is computed the way `ps` does it, as CPU
+/// time over the time since the process started, so it is a lifetime average
+/// rather than a recent reading. Fields whose file can't be read are left as is.
+pub(crate) fn fill_details(pid: u32, details: &mut ProcessDetails) {
+    if let Some(status) = read(pid, "status") {
+        let field = |key: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+                .map(str::trim)
+        };
+        // Uid: holds the real, effective, saved and filesystem IDs; ps reports the effective one
+        details.uid = field("Uid").and_then(|ids| ids.split_whitespace().nth(1)?.parse().ok());
+        if let Some(uid) = details.uid {
+            details.user = user_name(uid).unwrap_or_else(|| uid.to_string());
+        }
+        // Both are "<n> kB"; kernel threads have neither
+        let kb = |key: &str| field(key).and_then(|value| value.split_whitespace().next()?.parse().ok());
+        details.vsz_kb = kb("VmSize").unwrap_or(0);
+        details.rss_kb = kb("VmRSS").unwrap_or(0);
+        if let Some(total_kb) = mem_total_kb().filter(|&total| total > 0) {
+            details.mem = Some((details.rss_kb as f64 * 100.0 / total_kb as f64) as f32);
+        }
+    }
+
+    if let Some(stat) = read(pid, "stat") {
+        fill_from_stat(&stat, details);
+    }
+
+    // cmdline separates arguments with NULs; kernel threads have an empty one
+    if let Some(cmdline) = read(pid, "cmdline") {
+        let args: Vec<&str> = cmdline.split('\0').filter(|arg| !arg.is_empty()).collect();
+        details.command = args.join(" ");
+    }
+}
+
+/// `/proc/<pid>/stat` is `pid (comm) state ppid pgrp session ...`. `comm` may
+/// contain spaces and parentheses, so fields are counted from the last `)`.
+fn fill_from_stat(stat: &str, details: &mut ProcessDetails) {
+    let Some((_, rest)) = stat.rsplit_once(')') else {
+        return;
+    };
+    // fields[0] is field 3 (state) in proc(5) numbering
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let number = |index: usize| fields.get(index).and_then(|field| field.parse::<u64>().ok());
+
+    details.process_state = fields.first().map(|state| state.to_string()).unwrap_or_default();
+    details.ppid = number(1).unwrap_or(0) as u32;
+    details.pgid = number(2).unwrap_or(0) as u32;
+    details.sid = number(3).unwrap_or(0) as u32;
+    details.nice = fields.get(16).and_then(|nice| nice.parse().ok()).unwrap_or(0);
+    details.threads = number(17).unwrap_or(0) as u32;
+
+    let cpu_secs = (number(11).unwrap_or(0) + number(12).unwrap_or(0)) / CLOCK_TICKS_PER_SEC;
+    details.cpu_time_secs = cpu_secs;
+    details.cpu_time = format!("{:02}:{:02}:{:02}", cpu_secs / 3600, cpu_secs / 60 % 60, cpu_secs % 60);
+
+    let (Some(start_ticks), Some(system_uptime)) = (number(19), system_uptime_secs()) else {
+        return;
+    };
+    let elapsed = system_uptime - start_ticks as f64 / CLOCK_TICKS_PER_SEC as f64;
+    details.uptime_seconds = elapsed.max(0.0) as u64;
+    if elapsed > 0.0 {
+        details.cpu = Some((cpu_secs as f64 * 100.0 / elapsed) as f32);
+    }
+}
+
+fn read(pid: u32, file: &str) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/{}", pid, file)).ok()
+}
+
+/// First field of `/proc/uptime` (`"12345.67 23456.78"`): seconds since boot.
+fn system_uptime_secs() -> Option<f64> {
+    fs::read_to_string("/proc/uptime").ok()?.split_whitespace().next()?.parse().ok()
+}
+
+fn mem_total_kb() -> Option<u64> {
+    fs::read_to_string("/proc/meminfo")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Looks `uid` up in `/etc/passwd` (`name:x:uid:gid:...`). Accounts from other
+/// sources such as LDAP aren't found, and `ps` would print the number for those too.
+fn user_name(uid: u32) -> Option<String> {
+    let uid = uid.to_string();
+    fs::read_to_string("/etc/passwd").ok()?.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        (fields.nth(1)? == uid).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_from_stat_counts_fields_after_the_last_parenthesis() {
+        let stat = "4242 (tmux: server (1)) S 1 4242 4242 0 -1 4194624 1523 0 0 0 360000 6100 0 0 25 5 3 0 \
+                    98765 25165824 1200 18446744073709551615 1 1 0 0 0 0 0 3674112 134300677 0 0 0 17 2 0 0 0 0 0";
+        let mut details = ProcessDetails::default();
+        fill_from_stat(stat, &mut details);
+
+        assert_eq!(details.process_state, "S");
+        assert_eq!((details.ppid, details.pgid, details.sid), (1, 4242, 4242));
+        assert_eq!(details.nice, 5);
+        assert_eq!(details.threads, 3);
+        // utime + stime = 366100 ticks
+        assert_eq!(details.cpu_time_secs, 3661);
+        assert_eq!(details.cpu_time, "01:01:01");
+    }
+}
@@ -0,0 +1,83 @@
+//! The platform backend behind the listing commands and `kill_process`, held
+//! in managed state so commands don't call `lsof`/`netstat` directly and
+//! another implementation can be managed in its place.
+
+use super::error::{KillError, ListPortsError};
+use super::{collect_port_info, deliver_signal, PortInfo};
+
+pub(crate) trait ProcessInfoProvider {
+    /// Every listening TCP socket and bound UDP socket, grouped and sorted by name.
+    fn get_listening_ports(&self) -> Result<Vec<PortInfo>, ListPortsError>;
+
+    /// Delivers `signal` to `pid`; signal 0 only checks that the process exists.
+    fn kill_pid(&self, pid: u32, signal: i32) -> Result<(), KillError>;
+}
+
+/// How providers are held in managed state.
+pub(crate) type DynProvider = Box<dyn ProcessInfoProvider + Send + Sync>;
+
+/// `lsof` for sockets, `ps` for details and `kill` for signals.
+#[cfg(not(target_os = "windows"))]
+pub(crate) struct LsofProvider;
+
+#[cfg(not(target_os = "windows"))]
+impl ProcessInfoProvider for LsofProvider {
+    fn get_listening_ports(&self) -> Result<Vec<PortInfo>, ListPortsError> {
+        collect_port_info()
+    }
+
+    fn kill_pid(&self, pid: u32, signal: i32) -> Result<(), KillError> {
+        deliver_signal(pid, signal).map_err(|e| KillError::from_signal_error(pid, e))
+    }
+}
+
+/// `netstat` for sockets, `tasklist` for details and `taskkill` for signals.
+#[cfg(target_os = "windows")]
+pub(crate) struct NetstatProvider;
+
+#[cfg(target_os = "windows")]
+impl ProcessInfoProvider for NetstatProvider {
+    fn get_listening_ports(&self) -> Result<Vec<PortInfo>, ListPortsError> {
+        collect_port_info()
+    }
+
+    fn kill_pid(&self, pid: u32, signal: i32) -> Result<(), KillError> {
+        deliver_signal(pid, signal).map_err(|e| KillError::from_signal_error(pid, e))
+    }
+}
+
+/// The provider for the platform this was built for.
+pub(crate) fn platform_provider() -> DynProvider {
+    #[cfg(not(target_os = "windows"))]
+    let provider = Box::new(LsofProvider);
+    #[cfg(target_os = "windows")]
+    let provider = Box::new(NetstatProvider);
+
+    provider
+}
+
+/// Serves a fixed listing and records every signal instead of sending it, for
+/// tests. Signals to PIDs in `alive` succeed; others get `ProcessNotFound`.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MockProvider {
+    pub(crate) ports: Vec<PortInfo>,
+    pub(crate) alive: Vec<u32>,
+    pub(crate) signals: std::sync::Mutex<Vec<(u32, i32)>>,
+}
+
+#[cfg(test)]
+impl ProcessInfoProvider for MockProvider {
+    fn get_listening_ports(&self) -> Result<Vec<PortInfo>, ListPortsError> {
+        Ok(self.ports.clone())
+    }
+
+    fn kill_pid(&self, pid: u32, signal: i32) -> Result<(), KillError> {
+        self.signals.lock().unwrap().push((pid, signal));
+        if self.alive.contains(&pid) {
+            Ok(())
+        } else {
+            Err(KillError::ProcessNotFound(pid))
+        }
+    }
+}
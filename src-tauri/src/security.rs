@@ -0,0 +1,55 @@
+//! Code signing and sandbox status of macOS executables, from `codesign`. Too
+//! slow for every listing, so only looked up on request.
+
+use super::{inspect, ProcessSecurity};
+use std::process::Command;
+
+/// Inspects the executable `pid` is running.
+pub(crate) fn process_security(pid: u32) -> Result<ProcessSecurity, String> {
+    let exe_path = inspect::exe_path(pid)?;
+    let (is_signed, signing_authority) = signature(&exe_path);
+    Ok(ProcessSecurity {
+        pid,
+        exe_path: exe_path.clone(),
+        is_signed,
+        signing_authority,
+        sandbox_enabled: sandboxed(&exe_path),
+    })
+}
+
+/// Whether `exe_path` carries a valid signature, and the leaf signing authority
+/// (e.g. `"Apple Development: ..."` or `"Software Signing"`) if it has one.
+/// Ad-hoc signatures are signed but have no authority. `codesign` prints the
+/// details to stderr and exits non-zero for unsigned code; None if it couldn't run.
+pub(crate) fn signature(exe_path: &str) -> (Option<bool>, Option<String>) {
+    let Ok(output) = Command::new("codesign").args(["-dv", "--verbose=2", exe_path]).output() else {
+        return (None, None);
+    };
+    if !output.status.success() {
+        return (Some(false), None);
+    }
+
+    // The first Authority= line is the leaf certificate; the rest are its issuers
+    let authority = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .find_map(|line| line.strip_prefix("Authority="))
+        .map(str::to_string);
+    (Some(true), authority)
+}
+
+/// Whether `exe_path` is signed with the `com.apple.security.app-sandbox`
+/// entitlement set to true, which is what puts an app in the App Sandbox.
+fn sandboxed(exe_path: &str) -> bool {
+    // ":-" prints the entitlements as an XML plist to stdout
+    let Ok(output) = Command::new("codesign")
+        .args(["-d", "--entitlements", ":-", exe_path])
+        .output()
+    else {
+        return false;
+    };
+
+    let plist = String::from_utf8_lossy(&output.stdout);
+    plist
+        .split_once("<key>com.apple.security.app-sandbox</key>")
+        .is_some_and(|(_, rest)| rest.trim_start().starts_with("<true/>"))
+}
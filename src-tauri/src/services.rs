@@ -0,0 +1,151 @@
+//! Port number to service name lookup, backed by `/etc/services` with a
+//! built-in table for systems that lack it or leave common ports out.
+//!
+//! The resolver is process-wide rather than in `AppState` because names are
+//! filled in while building listings, including on the monitor threads.
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::LazyLock;
+
+/// Well-known ports that are worth labelling even without `/etc/services`.
+const COMMON_SERVICES: &[(u16, &str)] = &[
+    (20, "ftp-data"),
+    (21, "ftp"),
+    (22, "ssh"),
+    (23, "telnet"),
+    (25, "smtp"),
+    (53, "domain"),
+    (67, "bootps"),
+    (68, "bootpc"),
+    (80, "http"),
+    (110, "pop3"),
+    (123, "ntp"),
+    (143, "imap"),
+    (161, "snmp"),
+    (389, "ldap"),
+    (443, "https"),
+    (445, "microsoft-ds"),
+    (465, "submissions"),
+    (514, "syslog"),
+    (587, "submission"),
+    (631, "ipp"),
+    (993, "imaps"),
+    (995, "pop3s"),
+    (1433, "mssql"),
+    (1521, "oracle"),
+    (1883, "mqtt"),
+    (2049, "nfs"),
+    (2375, "docker"),
+    (2376, "docker-tls"),
+    (2379, "etcd"),
+    (3000, "dev-server"),
+    (3306, "mysql"),
+    (3389, "rdp"),
+    (4222, "nats"),
+    (5000, "upnp"),
+    (5353, "mdns"),
+    (5432, "postgres"),
+    (5672, "amqp"),
+    (5900, "vnc"),
+    (6379, "redis"),
+    (6443, "kubernetes-api"),
+    (7000, "afs3-fileserver"),
+    (8000, "http-alt"),
+    (8080, "http-alt"),
+    (8443, "https-alt"),
+    (9000, "cslistener"),
+    (9090, "prometheus"),
+    (9092, "kafka"),
+    (9200, "elasticsearch"),
+    (11211, "memcached"),
+    (27017, "mongodb"),
+];
+
+pub(crate) struct PortResolver {
+    names: HashMap<u16, String>,
+}
+
+impl PortResolver {
+    /// Reads `/etc/services` on top of the built-in table, whose names are all
+    /// that's available when the file is missing (e.g. on Windows).
+    pub(crate) fn new() -> Self {
+        let mut names: HashMap<u16, String> = COMMON_SERVICES
+            .iter()
+            .map(|(port, name)| (*port, name.to_string()))
+            .collect();
+
+        // /etc/services entries take precedence over the built-in names
+        if let Ok(contents) = fs::read_to_string("/etc/services") {
+            names.extend(parse_services(&contents));
+        }
+
+        PortResolver { names }
+    }
+
+    pub(crate) fn lookup(&self, port: u16) -> Option<&str> {
+        self.names.get(&port).map(String::as_str)
+    }
+}
+
+static RESOLVER: LazyLock<PortResolver> = LazyLock::new(PortResolver::new);
+
+/// Parses `/etc/services` lines such as `postgresql  5432/tcp  postgres  # comment`,
+/// keeping the first name listed for each port.
+fn parse_services(contents: &str) -> HashMap<u16, String> {
+    let mut services = HashMap::new();
+
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some(port_proto)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some(Ok(port)) = port_proto.split('/').next().map(str::parse::<u16>) else {
+            continue;
+        };
+        services.entry(port).or_insert_with(|| name.to_string());
+    }
+
+    services
+}
+
+/// Parses the services table now rather than on the first lookup.
+pub(crate) fn preload() {
+    LazyLock::force(&RESOLVER);
+}
+
+pub(crate) fn port_to_service_name(port: u16) -> Option<String> {
+    RESOLVER.lookup(port).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_services_keeps_the_first_name_per_port() {
+        let contents = "\
+# Network services, Internet style
+#
+tcpmux          1/tcp                           # TCP port service multiplexer
+http            80/tcp          www             # WorldWideWeb HTTP
+http            80/udp
+https           443/tcp
+postgresql      5432/tcp        postgres        # PostgreSQL Database
+domain          53/tcp                          # Domain Name Server
+domain          53/udp
+mdns            5353/udp                        # Multicast DNS
+kerberos        88/tcp          kerberos5 krb5 kerberos-sec
+kerberos5       88/udp          kerberos krb5
+bogus           99999/tcp
+";
+        let services = parse_services(contents);
+
+        assert_eq!(services.get(&80).map(String::as_str), Some("http"));
+        assert_eq!(services.get(&5432).map(String::as_str), Some("postgresql"));
+        assert_eq!(services.get(&5353).map(String::as_str), Some("mdns"));
+        assert_eq!(services.get(&88).map(String::as_str), Some("kerberos"));
+        assert_eq!(services.len(), 7, "comment lines and out-of-range ports are skipped");
+    }
+}
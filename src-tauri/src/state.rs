@@ -0,0 +1,416 @@
+//! App-wide managed state: a short-lived cache of the `list_ports` listing so
+//! rapid frontend calls (e.g. a live filter) don't each spawn a fresh `lsof`,
+//! indexed by port for `find_process_by_port`,
+//! a limit on how many listings are collected at once,
+//! plus settings for the inspection commands, the kill audit log, the
+//! SIGKILL escalations `kill_graceful_async` has scheduled, the listings
+//! saved by `take_snapshot`, the pinned PIDs and the kill policy.
+
+use super::audit::{self, AuditLog};
+use super::error::ListPortsError;
+use super::watchlist::Watchlist;
+use super::{KillPolicy, PortInfo, SnapshotMeta};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a cached listing is served before `lsof` runs again.
+pub(crate) const DEFAULT_CACHE_TTL_MS: u64 = 2000;
+
+/// Environment variable name fragments whose values `get_process_env` redacts.
+pub(crate) const DEFAULT_ENV_BLOCKLIST: &[&str] = &["SECRET", "PASSWORD", "TOKEN", "KEY", "CREDENTIAL"];
+
+/// Process names the kill policy protects until `set_kill_policy` says otherwise:
+/// the init systems and the Windows processes whose loss takes the session down.
+pub(crate) const DEFAULT_PROTECTED_NAMES: &[&str] =
+    &["init", "systemd", "launchd", "kernel_task", "wininit.exe", "csrss.exe"];
+
+/// File name of the kill audit log inside the app data directory.
+pub(crate) const AUDIT_LOG_FILE: &str = "kill-audit.ndjson";
+
+/// File name of the pinned PID list, next to the audit log.
+pub(crate) const WATCHLIST_FILE: &str = "watchlist.json";
+
+/// How many listings may be collected at once. Concurrent collections all run
+/// the same `lsof`, so by default later callers wait for the first one's result.
+pub(crate) const DEFAULT_MAX_CONCURRENT_COLLECTIONS: u32 = 1;
+
+/// How many `take_snapshot` listings are kept before the oldest is dropped.
+pub(crate) const MAX_SNAPSHOTS: usize = 20;
+
+#[derive(Debug, Clone, Copy)]
+struct CollectionLimit {
+    max_concurrent: u32,
+    /// Whether callers over the limit wait for a slot or fail with `Busy`.
+    queue_on_busy: bool,
+}
+
+/// The last listing, when it was collected, and the group listening on each of
+/// its ports.
+struct PortCache {
+    last_updated: Instant,
+    ports: Vec<PortInfo>,
+    /// Every PID with a socket on the port, merged into the first group that
+    /// has one, as `find_process_by_port` returns them.
+    by_port: HashMap<u16, PortInfo>,
+}
+
+impl PortCache {
+    fn new(ports: Vec<PortInfo>) -> Self {
+        let mut by_port: HashMap<u16, PortInfo> = HashMap::new();
+        for port_info in &ports {
+            for pid_info in &port_info.pids {
+                for &port in &pid_info.ports {
+                    let group = by_port.entry(port).or_insert_with(|| PortInfo {
+                        pids: Vec::new(),
+                        ..port_info.clone()
+                    });
+                    if !group.pids.contains(pid_info) {
+                        group.pids.push(pid_info.clone());
+                    }
+                }
+            }
+        }
+
+        PortCache {
+            last_updated: Instant::now(),
+            ports,
+            by_port,
+        }
+    }
+}
+
+pub(crate) struct AppState {
+    cache: Mutex<Option<PortCache>>,
+    cache_ttl_ms: AtomicU64,
+    collection_limit: Mutex<CollectionLimit>,
+    /// Listings being collected right now, signalled on `collection_finished`.
+    collections_running: Mutex<u32>,
+    collection_finished: Condvar,
+    /// Numbers `cached_ports` calls, to tell their log lines apart.
+    next_request_id: Mutex<u64>,
+    /// The listing `list_ports_diff` last returned changes against.
+    snapshot: Mutex<Option<Vec<PortInfo>>>,
+    /// Uppercase fragments; any variable whose name contains one is redacted.
+    env_blocklist: Vec<String>,
+    audit_log: AuditLog,
+    /// Scheduled escalations by PID. Dropping the sender cancels one; the number
+    /// tells a finished escalation apart from a newer one for the same PID.
+    pending_kills: Mutex<HashMap<u32, (u64, Sender<()>)>>,
+    next_kill_id: Mutex<u64>,
+    /// Listings saved by `take_snapshot`, oldest first.
+    snapshots: Mutex<Vec<(SnapshotMeta, Vec<PortInfo>)>>,
+    watchlist: Watchlist,
+    kill_policy: Mutex<KillPolicy>,
+}
+
+/// Deletes the directory `AppState::for_tests` keeps its files in once dropped,
+/// so bind it to a variable for the whole test rather than to `_`.
+#[cfg(test)]
+pub(crate) struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+impl AppState {
+    /// A `cache_ttl_ms` of zero disables caching. `env_blocklist` entries match
+    /// case-insensitively anywhere in a variable name. Kill actions are appended
+    /// to the NDJSON file at `audit_log_path`; pinned PIDs are loaded from and
+    /// saved to `watchlist_path`.
+    pub(crate) fn new(
+        cache_ttl_ms: u64,
+        env_blocklist: &[&str],
+        audit_log_path: PathBuf,
+        watchlist_path: PathBuf,
+    ) -> Self {
+        AppState {
+            cache: Mutex::new(None),
+            cache_ttl_ms: AtomicU64::new(cache_ttl_ms),
+            collection_limit: Mutex::new(CollectionLimit {
+                max_concurrent: DEFAULT_MAX_CONCURRENT_COLLECTIONS,
+                queue_on_busy: true,
+            }),
+            collections_running: Mutex::new(0),
+            collection_finished: Condvar::new(),
+            next_request_id: Mutex::new(0),
+            snapshot: Mutex::new(None),
+            env_blocklist: env_blocklist.iter().map(|entry| entry.to_uppercase()).collect(),
+            audit_log: AuditLog::new(audit_log_path),
+            pending_kills: Mutex::new(HashMap::new()),
+            next_kill_id: Mutex::new(0),
+            snapshots: Mutex::new(Vec::new()),
+            watchlist: Watchlist::load(watchlist_path),
+            kill_policy: Mutex::new(KillPolicy::default()),
+        }
+    }
+
+    /// Default settings, with the audit log and watchlist in a fresh temporary
+    /// directory that is removed when the returned `TestDir` is dropped.
+    #[cfg(test)]
+    pub(crate) fn for_tests() -> (Self, TestDir) {
+        let dir = std::env::temp_dir().join(format!("process-monitor-test-{}", uuid::Uuid::new_v4()));
+        let state = AppState::new(
+            DEFAULT_CACHE_TTL_MS,
+            DEFAULT_ENV_BLOCKLIST,
+            dir.join(AUDIT_LOG_FILE),
+            dir.join(WATCHLIST_FILE),
+        );
+        (state, TestDir(dir))
+    }
+
+    pub(crate) fn audit_log(&self) -> &AuditLog {
+        &self.audit_log
+    }
+
+    pub(crate) fn watchlist(&self) -> &Watchlist {
+        &self.watchlist
+    }
+
+    pub(crate) fn cache_ttl_ms(&self) -> u64 {
+        self.cache_ttl_ms.load(Ordering::Relaxed)
+    }
+
+    /// Takes effect for the listing already cached too. Zero disables caching.
+    pub(crate) fn set_cache_ttl_ms(&self, cache_ttl_ms: u64) {
+        self.cache_ttl_ms.store(cache_ttl_ms, Ordering::Relaxed);
+    }
+
+    pub(crate) fn kill_policy(&self) -> KillPolicy {
+        self.kill_policy.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub(crate) fn set_kill_policy(&self, policy: KillPolicy) {
+        *self.kill_policy.lock().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    /// Whether the value of environment variable `name` should be hidden.
+    pub(crate) fn is_sensitive_env_var(&self, name: &str) -> bool {
+        let name = name.to_uppercase();
+        self.env_blocklist.iter().any(|entry| name.contains(entry.as_str()))
+    }
+
+    /// Returns the cached listing if it is younger than the TTL, otherwise
+    /// runs `collect` and caches its result. Errors are never cached.
+    ///
+    /// At most `max_concurrent` calls collect at once. The rest either wait and
+    /// then usually find the listing a finished call just cached, or fail with
+    /// `ListPortsError::Busy`, as `configure_collection_limit` chose.
+    pub(crate) fn cached_ports<E: From<ListPortsError>>(
+        &self,
+        collect: impl FnOnce() -> Result<Vec<PortInfo>, E>,
+    ) -> Result<Vec<PortInfo>, E> {
+        self.tracked_ports(collect).map(|(_, ports)| ports)
+    }
+
+    /// `cached_ports`, along with the request ID this call was given, which is
+    /// the one a `ListPortsError::Busy` from it reports.
+    pub(crate) fn tracked_ports<E: From<ListPortsError>>(
+        &self,
+        collect: impl FnOnce() -> Result<Vec<PortInfo>, E>,
+    ) -> Result<(u64, Vec<PortInfo>), E> {
+        self.read_cache(collect, |cache| cache.ports.clone())
+    }
+
+    /// Returns the processes listening on `port` from the listing `cached_ports`
+    /// would return, without scanning it.
+    pub(crate) fn cached_port<E: From<ListPortsError>>(
+        &self,
+        port: u16,
+        collect: impl FnOnce() -> Result<Vec<PortInfo>, E>,
+    ) -> Result<Option<PortInfo>, E> {
+        self.read_cache(collect, |cache| cache.by_port.get(&port).cloned())
+            .map(|(_, port_info)| port_info)
+    }
+
+    /// Runs `collect` regardless of the cache, for callers that need a listing
+    /// of this instant, under the same collection limit as `cached_ports`. The
+    /// result is cached for the next `cached_ports` call.
+    pub(crate) fn fresh_ports<E: From<ListPortsError>>(
+        &self,
+        collect: impl FnOnce() -> Result<Vec<PortInfo>, E>,
+    ) -> Result<Vec<PortInfo>, E> {
+        let request_id = self.next_request_id();
+        let _slot = self.acquire_collection_slot(request_id)?;
+
+        tracing::debug!("Request {} collecting a fresh listing", request_id);
+        let ports = collect()?;
+        *self.cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(PortCache::new(ports.clone()));
+        Ok(ports)
+    }
+
+    fn next_request_id(&self) -> u64 {
+        let mut next_id = self.next_request_id.lock().unwrap_or_else(|e| e.into_inner());
+        *next_id += 1;
+        *next_id
+    }
+
+    /// Runs `read` on a cache that is younger than the TTL, collecting a fresh
+    /// listing first if needed, for `cached_ports` and `cached_port`. Returns
+    /// the request ID along with the result.
+    fn read_cache<R, E: From<ListPortsError>>(
+        &self,
+        collect: impl FnOnce() -> Result<Vec<PortInfo>, E>,
+        read: impl Fn(&PortCache) -> R,
+    ) -> Result<(u64, R), E> {
+        let request_id = self.next_request_id();
+
+        if let Some(result) = self.read_fresh_cache(&read) {
+            tracing::debug!("Request {} served from the cache", request_id);
+            return Ok((request_id, result));
+        }
+
+        let _slot = self.acquire_collection_slot(request_id)?;
+        if let Some(result) = self.read_fresh_cache(&read) {
+            tracing::debug!("Request {} served from a listing collected while it waited", request_id);
+            return Ok((request_id, result));
+        }
+
+        tracing::debug!("Request {} collecting a fresh listing", request_id);
+        let cache = PortCache::new(collect()?);
+        let result = read(&cache);
+        *self.cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(cache);
+        Ok((request_id, result))
+    }
+
+    /// `read` applied to the cache, if it is younger than the TTL.
+    fn read_fresh_cache<R>(&self, read: impl Fn(&PortCache) -> R) -> Option<R> {
+        let ttl = Duration::from_millis(self.cache_ttl_ms());
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.as_ref().filter(|cache| cache.last_updated.elapsed() < ttl).map(read)
+    }
+
+    /// Sets how many listings may be collected at once and what callers over the
+    /// limit do. Callers already waiting re-check against the new limit.
+    pub(crate) fn configure_collection_limit(&self, max_concurrent: u32, queue_on_busy: bool) {
+        // Held across the update so a waiter can't check the old limit, then
+        // miss the notification before it starts waiting
+        let _running = self.collections_running.lock().unwrap_or_else(|e| e.into_inner());
+        *self.collection_limit.lock().unwrap_or_else(|e| e.into_inner()) = CollectionLimit {
+            max_concurrent,
+            queue_on_busy,
+        };
+        self.collection_finished.notify_all();
+    }
+
+    /// Waits for (or, without `queue_on_busy`, refuses) a collection slot, which
+    /// is given back when the returned guard is dropped.
+    fn acquire_collection_slot(&self, request_id: u64) -> Result<CollectionSlot<'_>, ListPortsError> {
+        let mut running = self.collections_running.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let limit = *self.collection_limit.lock().unwrap_or_else(|e| e.into_inner());
+            if *running < limit.max_concurrent {
+                break;
+            }
+            if !limit.queue_on_busy {
+                return Err(ListPortsError::Busy { request_id });
+            }
+            tracing::debug!("Request {} waiting for one of {} running collections", request_id, *running);
+            running = self.collection_finished.wait(running).unwrap_or_else(|e| e.into_inner());
+        }
+
+        *running += 1;
+        Ok(CollectionSlot { state: self })
+    }
+
+    /// Stores `ports` as the `list_ports_diff` baseline, returning the previous one.
+    pub(crate) fn replace_snapshot(&self, ports: Vec<PortInfo>) -> Option<Vec<PortInfo>> {
+        self.snapshot.lock().unwrap_or_else(|e| e.into_inner()).replace(ports)
+    }
+
+    /// Saves `ports` under a fresh id, dropping the oldest saved listing once
+    /// there are more than `MAX_SNAPSHOTS`.
+    pub(crate) fn save_snapshot(&self, ports: Vec<PortInfo>) -> SnapshotMeta {
+        let meta = SnapshotMeta {
+            id: uuid::Uuid::new_v4().to_string(),
+            taken_at: audit::utc_timestamp(SystemTime::now()),
+            group_count: ports.len(),
+        };
+
+        let mut snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        snapshots.push((meta.clone(), ports));
+        if snapshots.len() > MAX_SNAPSHOTS {
+            snapshots.remove(0);
+        }
+        meta
+    }
+
+    /// The listing saved under `id`, if it hasn't been dropped.
+    pub(crate) fn saved_snapshot(&self, id: &str) -> Option<Vec<PortInfo>> {
+        let snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        snapshots.iter().find(|(meta, _)| meta.id == id).map(|(_, ports)| ports.clone())
+    }
+
+    /// Ids and timestamps of the saved listings, oldest first.
+    pub(crate) fn snapshot_metas(&self) -> Vec<SnapshotMeta> {
+        let snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        snapshots.iter().map(|(meta, _)| meta.clone()).collect()
+    }
+
+    /// Drops the cached listing so the next `list_ports` call runs `lsof`, and
+    /// the `list_ports_diff` baseline so its next call reports everything as added.
+    pub(crate) fn clear_cache(&self) {
+        self.invalidate_cache();
+        *self.snapshot.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Drops only the cached listing, for kills: the `list_ports_diff` baseline
+    /// stays so its next call reports the killed process as removed.
+    pub(crate) fn invalidate_cache(&self) {
+        *self.cache.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Registers an escalation for `pid`, returning its id for `finish_pending_kill`
+    /// and a receiver that disconnects when `cancel_pending_kill` is called.
+    /// Fails if one is already pending for `pid`.
+    pub(crate) fn start_pending_kill(&self, pid: u32) -> Result<(u64, Receiver<()>), String> {
+        let mut pending = self.pending_kills.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.contains_key(&pid) {
+            return Err(format!("A kill is already pending for PID {}", pid));
+        }
+
+        let mut next_id = self.next_kill_id.lock().unwrap_or_else(|e| e.into_inner());
+        let id = *next_id;
+        *next_id += 1;
+
+        let (cancel, cancelled) = mpsc::channel();
+        pending.insert(pid, (id, cancel));
+        Ok((id, cancelled))
+    }
+
+    /// Cancels the escalation pending for `pid`. Returns false if there was none.
+    pub(crate) fn cancel_pending_kill(&self, pid: u32) -> bool {
+        self.pending_kills
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&pid)
+            .is_some()
+    }
+
+    /// Forgets escalation `id` for `pid` once it has run its course, leaving any
+    /// newer escalation started for the same PID after a cancel in place.
+    pub(crate) fn finish_pending_kill(&self, pid: u32, id: u64) {
+        let mut pending = self.pending_kills.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.get(&pid).is_some_and(|(pending_id, _)| *pending_id == id) {
+            pending.remove(&pid);
+        }
+    }
+}
+
+/// A held collection slot; dropping it lets the next waiting caller collect.
+struct CollectionSlot<'a> {
+    state: &'a AppState,
+}
+
+impl Drop for CollectionSlot<'_> {
+    fn drop(&mut self) {
+        *self.state.collections_running.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        self.state.collection_finished.notify_one();
+    }
+}
@@ -0,0 +1,114 @@
+//! Machine-wide load and memory figures for the overview card, as opposed to
+//! the per-process details everything else reports.
+
+use super::SystemOverview;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+/// Reads `/proc/loadavg` (`"0.52 0.58 0.59 1/123 4567"`) and `/proc/meminfo`.
+/// Free memory is `MemAvailable`, which counts reclaimable page cache, rather
+/// than the much smaller `MemFree`.
+#[cfg(target_os = "linux")]
+pub(crate) fn overview() -> Result<SystemOverview, String> {
+    let loadavg = fs::read_to_string("/proc/loadavg").map_err(|e| format!("Failed to read /proc/loadavg: {}", e))?;
+    let meminfo = fs::read_to_string("/proc/meminfo").map_err(|e| format!("Failed to read /proc/meminfo: {}", e))?;
+
+    let (load_avg_1m, load_avg_5m, load_avg_15m) = parse_load_averages(&loadavg)?;
+
+    // Rows look like "MemTotal:       16314352 kB"
+    let meminfo_kb = |key: &str| -> Result<u64, String> {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|rest| rest.split_whitespace().next()?.parse().ok())
+            .ok_or_else(|| format!("/proc/meminfo has no {} entry", key))
+    };
+    let total_memory_kb = meminfo_kb("MemTotal")?;
+    let free_memory_kb = meminfo_kb("MemAvailable")?;
+
+    Ok(SystemOverview {
+        cpu_count: cpu_count(),
+        load_avg_1m,
+        load_avg_5m,
+        load_avg_15m,
+        total_memory_kb,
+        used_memory_kb: total_memory_kb.saturating_sub(free_memory_kb),
+        free_memory_kb,
+    })
+}
+
+/// Reads `sysctl -n vm.loadavg` (`"{ 1.23 1.45 1.67 }"`) and `hw.memsize`, and
+/// counts free plus speculative pages from `vm_stat` as free memory.
+#[cfg(target_os = "macos")]
+pub(crate) fn overview() -> Result<SystemOverview, String> {
+    let loadavg = run("sysctl", &["-n", "vm.loadavg"])?;
+    let (load_avg_1m, load_avg_5m, load_avg_15m) =
+        parse_load_averages(loadavg.trim().trim_start_matches('{').trim_end_matches('}'))?;
+
+    let total_memory_kb = run("sysctl", &["-n", "hw.memsize"])?
+        .trim()
+        .parse::<u64>()
+        .map_err(|e| format!("Failed to parse hw.memsize: {}", e))?
+        / 1024;
+
+    // "Mach Virtual Memory Statistics: (page size of 16384 bytes)" then rows like "Pages free:  12345."
+    let vm_stat = run("vm_stat", &[])?;
+    let page_size: u64 = vm_stat
+        .split("page size of ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next()?.parse().ok())
+        .unwrap_or(4096);
+    let pages = |key: &str| -> u64 {
+        vm_stat
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|rest| rest.trim().trim_end_matches('.').parse().ok())
+            .unwrap_or(0)
+    };
+    let free_memory_kb = (pages("Pages free:") + pages("Pages speculative:")) * page_size / 1024;
+
+    Ok(SystemOverview {
+        cpu_count: cpu_count(),
+        load_avg_1m,
+        load_avg_5m,
+        load_avg_15m,
+        total_memory_kb,
+        used_memory_kb: total_memory_kb.saturating_sub(free_memory_kb),
+        free_memory_kb,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn overview() -> Result<SystemOverview, String> {
+    Err("System overview is not yet implemented on this platform".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn run(tool: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(tool)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", tool, e))?;
+    if !output.status.success() {
+        return Err(format!("{} command failed", tool));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parses the first three whitespace-separated numbers of a load average line.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn parse_load_averages(line: &str) -> Result<(f64, f64, f64), String> {
+    let mut averages = line.split_whitespace().map(str::parse::<f64>);
+    match (averages.next(), averages.next(), averages.next()) {
+        (Some(Ok(one)), Some(Ok(five)), Some(Ok(fifteen))) => Ok((one, five, fifteen)),
+        _ => Err(format!("Failed to parse load averages from {:?}", line.trim())),
+    }
+}
+
+/// Logical CPUs available to this process.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn cpu_count() -> u32 {
+    std::thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(1)
+}
@@ -0,0 +1,269 @@
+//! Locations of the `lsof` and `ps` binaries the Unix backend runs, how long
+//! they may run, and which columns `ps` is asked for. The paths default to the
+//! bare names, resolved through PATH, and can be overridden at runtime with
+//! `configure_paths` for installs outside PATH (e.g. `/opt/homebrew/bin`); the
+//! timeouts with `configure_timeouts` and the columns with `set_ps_format`.
+//!
+//! Held in process-wide statics rather than in `AppState` because the call sites
+//! include the monitor threads and helpers that never see managed state.
+
+use super::{DependencyStatus, ToolStatus, PS_WORD_COLUMNS};
+use std::env;
+use std::io::{self, Read};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long `lsof` may run before it is killed. It can hang on a stale NFS
+/// mount or an unresponsive network interface.
+pub(crate) const DEFAULT_LSOF_TIMEOUT_MS: u64 = 5000;
+
+/// How long each per-PID `ps` call may run before it is killed.
+pub(crate) const DEFAULT_PS_TIMEOUT_MS: u64 = 1000;
+
+/// How often `output_within` checks whether the child has exited.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static LSOF_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_LSOF_TIMEOUT_MS);
+static PS_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_PS_TIMEOUT_MS);
+
+struct ToolPaths {
+    lsof: PathBuf,
+    ps: PathBuf,
+}
+
+static TOOL_PATHS: LazyLock<RwLock<ToolPaths>> = LazyLock::new(|| {
+    RwLock::new(ToolPaths {
+        lsof: PathBuf::from("lsof"),
+        ps: PathBuf::from("ps"),
+    })
+});
+
+/// Word columns requested from `ps`, all taken from `PS_WORD_COLUMNS` or
+/// `EXTRA_PS_COLUMNS`.
+static PS_COLUMNS: LazyLock<RwLock<Vec<&'static str>>> = LazyLock::new(|| RwLock::new(PS_WORD_COLUMNS.to_vec()));
+
+/// A `Command` for the configured `lsof`.
+pub(crate) fn lsof() -> Command {
+    Command::new(&TOOL_PATHS.read().unwrap_or_else(|e| e.into_inner()).lsof)
+}
+
+/// A `Command` for the configured `ps`.
+pub(crate) fn ps() -> Command {
+    Command::new(&TOOL_PATHS.read().unwrap_or_else(|e| e.into_inner()).ps)
+}
+
+/// How to install `tool` on the common platforms, for tools a listing can't do without.
+pub(crate) fn install_hint(tool: &str) -> Option<&'static str> {
+    match tool {
+        "lsof" => Some("brew install lsof (macOS) / apt-get install lsof (Debian) / yum install lsof (RHEL)"),
+        "ps" => Some("apt-get install procps (Debian) / yum install procps-ng (RHEL)"),
+        _ => None,
+    }
+}
+
+/// Describes a failure to spawn `tool`, pointing at the package to install
+/// when the binary is missing.
+pub(crate) fn spawn_error(tool: &str, e: &io::Error) -> String {
+    match install_hint(tool) {
+        Some(hint) if e.kind() == io::ErrorKind::NotFound => {
+            format!("{} is not installed. Install it with: {}", tool, hint)
+        }
+        _ => format!("Failed to execute {}: {}", tool, e),
+    }
+}
+
+/// Replaces both tool paths. Callers validate them with `check_executable` first.
+pub(crate) fn configure(lsof: PathBuf, ps: PathBuf) {
+    *TOOL_PATHS.write().unwrap_or_else(|e| e.into_inner()) = ToolPaths { lsof, ps };
+}
+
+/// The configured `lsof` and `ps` paths, as given to `configure`.
+pub(crate) fn paths() -> (PathBuf, PathBuf) {
+    let paths = TOOL_PATHS.read().unwrap_or_else(|e| e.into_inner());
+    (paths.lsof.clone(), paths.ps.clone())
+}
+
+pub(crate) fn lsof_timeout_ms() -> u64 {
+    LSOF_TIMEOUT_MS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn ps_timeout_ms() -> u64 {
+    PS_TIMEOUT_MS.load(Ordering::Relaxed)
+}
+
+/// Replaces both timeouts. Callers reject zero first.
+pub(crate) fn configure_timeouts(lsof_ms: u64, ps_ms: u64) {
+    LSOF_TIMEOUT_MS.store(lsof_ms, Ordering::Relaxed);
+    PS_TIMEOUT_MS.store(ps_ms, Ordering::Relaxed);
+}
+
+pub(crate) fn ps_columns() -> Vec<&'static str> {
+    PS_COLUMNS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replaces the `ps` word columns. Callers check them against the known ones first.
+pub(crate) fn configure_ps_columns(columns: Vec<&'static str>) {
+    *PS_COLUMNS.write().unwrap_or_else(|e| e.into_inner()) = columns;
+}
+
+/// Like `Command::output`, but kills the child and fails with
+/// `io::ErrorKind::TimedOut` if it hasn't exited after `timeout_ms`.
+pub(crate) fn output_within(command: &mut Command, timeout_ms: u64) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Read on their own threads so a child with a lot to say can't block on a full pipe
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out after {} ms", timeout_ms),
+            ));
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL.min(deadline - now));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Checks that `tool` names an executable file. Bare names are looked up on PATH
+/// the way `Command` would; anything containing a separator is checked as is.
+pub(crate) fn check_executable(tool: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(tool);
+    match resolve(&path) {
+        Some(_) => Ok(path),
+        None => Err(format!("{} is not an executable file", tool)),
+    }
+}
+
+/// The executable `path` refers to, looking bare names up on PATH.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if path.components().count() > 1 {
+        return is_executable(path).then(|| path.to_path_buf());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(path))
+        .find(|candidate| is_executable(candidate))
+}
+
+/// Tools the backend can't list or kill without, and those only some commands
+/// use (e.g. `pgrep` for `kill_by_name`), on this platform.
+#[cfg(not(target_os = "freebsd"))]
+const REQUIRED_TOOLS: &[&str] = &["lsof", "ps", "kill"];
+#[cfg(target_os = "freebsd")]
+const REQUIRED_TOOLS: &[&str] = &["sockstat", "ps", "kill"];
+#[cfg(target_os = "linux")]
+const OPTIONAL_TOOLS: &[&str] = &["pgrep", "pmap", "taskset"];
+#[cfg(target_os = "macos")]
+const OPTIONAL_TOOLS: &[&str] = &["pgrep", "vmmap", "nettop", "codesign", "launchctl"];
+// lsof only backs the per-process views (open files, connections) here
+#[cfg(target_os = "freebsd")]
+const OPTIONAL_TOOLS: &[&str] = &["lsof", "pgrep"];
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+const OPTIONAL_TOOLS: &[&str] = &["pgrep"];
+
+/// Looks up every external binary the backend runs: where it is and which
+/// version it reports. `lsof` and `ps` are checked at their configured paths.
+pub(crate) fn dependency_status() -> DependencyStatus {
+    let paths = TOOL_PATHS.read().unwrap_or_else(|e| e.into_inner());
+    let tool_status = |name: &str, required: bool| {
+        let configured = match name {
+            "lsof" => paths.lsof.clone(),
+            "ps" => paths.ps.clone(),
+            _ => PathBuf::from(name),
+        };
+        let path = resolve(&configured);
+        ToolStatus {
+            name: name.to_string(),
+            required,
+            version: path.as_deref().and_then(|path| version(name, path)),
+            path: path.map(|path| path.to_string_lossy().into_owned()),
+            install_hint: install_hint(name).map(str::to_string),
+        }
+    };
+
+    let tools: Vec<ToolStatus> = REQUIRED_TOOLS
+        .iter()
+        .map(|name| tool_status(name, true))
+        .chain(OPTIONAL_TOOLS.iter().map(|name| tool_status(name, false)))
+        .collect();
+    DependencyStatus {
+        all_required_found: tools.iter().all(|tool| !tool.required || tool.path.is_some()),
+        tools,
+    }
+}
+
+/// The version `tool` reports, if it has a way to. `lsof -v` prints a
+/// `revision: 4.95.0` line to stderr; procps and util-linux tools answer
+/// `--version` with e.g. `ps from procps-ng 4.0.2`. The BSD tools on macOS
+/// have no version flag at all.
+fn version(tool: &str, path: &Path) -> Option<String> {
+    let args: &[&str] = if tool == "lsof" { &["-v"] } else { &["--version"] };
+    let output = output_within(Command::new(path).args(args), ps_timeout_ms()).ok()?;
+    let text = [output.stdout, output.stderr].concat();
+    let text = String::from_utf8_lossy(&text);
+
+    if tool == "lsof" {
+        return text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("revision:"))
+            .map(|revision| revision.trim().to_string());
+    }
+    if !output.status.success() {
+        return None;
+    }
+    text.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string)
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_error_suggests_a_package_only_when_the_tool_is_missing() {
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+        let message = spawn_error("lsof", &missing);
+        assert!(message.starts_with("lsof is not installed. Install it with: "), "{}", message);
+        assert!(message.contains("apt-get install lsof"));
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(spawn_error("lsof", &denied).starts_with("Failed to execute lsof: "));
+        assert!(spawn_error("sockstat", &missing).starts_with("Failed to execute sockstat: "));
+    }
+}
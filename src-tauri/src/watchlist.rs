@@ -0,0 +1,62 @@
+//! PIDs the user pinned to the top of the listing, saved as a JSON array so
+//! they stay pinned across restarts of the app.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub(crate) struct Watchlist {
+    path: PathBuf,
+    /// In the order they were pinned.
+    pids: Mutex<Vec<u32>>,
+}
+
+impl Watchlist {
+    /// Loads the watchlist saved at `path`. A missing or unreadable file gives
+    /// an empty list; PIDs are only meaningful until reboot anyway.
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let pids = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable watchlist {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Watchlist {
+            path,
+            pids: Mutex::new(pids),
+        }
+    }
+
+    pub(crate) fn pids(&self) -> Vec<u32> {
+        self.pids.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Adds `pid` and saves the list. Pinning an already pinned PID does nothing.
+    pub(crate) fn pin(&self, pid: u32) -> Result<(), String> {
+        let mut pids = self.pids.lock().unwrap_or_else(|e| e.into_inner());
+        if pids.contains(&pid) {
+            return Ok(());
+        }
+        pids.push(pid);
+        self.save(&pids)
+    }
+
+    /// Removes `pid` and saves the list. Fails if it wasn't pinned.
+    pub(crate) fn unpin(&self, pid: u32) -> Result<(), String> {
+        let mut pids = self.pids.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(index) = pids.iter().position(|&pinned| pinned == pid) else {
+            return Err(format!("PID {} is not pinned", pid));
+        };
+        pids.remove(index);
+        self.save(&pids)
+    }
+
+    fn save(&self, pids: &[u32]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create watchlist directory: {}", e))?;
+        }
+        let contents = serde_json::to_string(pids).map_err(|e| format!("Failed to serialize watchlist: {}", e))?;
+        fs::write(&self.path, contents).map_err(|e| format!("Failed to save watchlist: {}", e))
+    }
+}
@@ -0,0 +1,282 @@
+//! Windows backend: `netstat -ano` for sockets, `tasklist` for process details
+//! and `taskkill` for termination, since `lsof` and `ps` are not available.
+
+use super::{split_address, ListPortsError, ProcessDetails, SignalError, SocketEntry};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::process::{Command, Output};
+
+#[derive(Debug)]
+pub(crate) enum ToolError {
+    /// The tool is not installed or not on PATH.
+    NotFound(&'static str),
+    /// The tool exists but could not be spawned.
+    Spawn { tool: &'static str, error: io::Error },
+    /// The tool ran but exited unsuccessfully.
+    Failed { tool: &'static str, stderr: String },
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::NotFound(tool) => write!(f, "{} was not found on PATH", tool),
+            ToolError::Spawn { tool, error } => write!(f, "Failed to execute {}: {}", tool, error),
+            ToolError::Failed { tool, stderr } => write!(f, "{} command failed: {}", tool, stderr),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<ToolError> for String {
+    fn from(error: ToolError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<ToolError> for SignalError {
+    fn from(error: ToolError) -> Self {
+        match error {
+            ToolError::Failed { stderr, .. } => SignalError::Failed(stderr),
+            other => SignalError::Spawn(other.to_string()),
+        }
+    }
+}
+
+impl From<ToolError> for ListPortsError {
+    fn from(error: ToolError) -> Self {
+        match error {
+            ToolError::NotFound(tool) => ListPortsError::ToolNotFound(tool.to_string()),
+            ToolError::Spawn { .. } => ListPortsError::SpawnError(error.to_string()),
+            ToolError::Failed { tool, stderr } => ListPortsError::CommandFailed {
+                tool: tool.to_string(),
+                stderr,
+            },
+        }
+    }
+}
+
+/// Spawns `tool` and waits for it, mapping a missing binary to `ToolError::NotFound`.
+pub(crate) fn run_tool(tool: &'static str, args: &[&str]) -> Result<Output, ToolError> {
+    Command::new(tool).args(args).output().map_err(|error| {
+        tracing::warn!("Failed to execute {}: {}", tool, error);
+        match error.kind() {
+            io::ErrorKind::NotFound => ToolError::NotFound(tool),
+            _ => ToolError::Spawn { tool, error },
+        }
+    })
+}
+
+/// Collects sockets from `netstat -ano` along with the details of every owning PID.
+///
+/// With `listening_only`, TCP sockets are limited to the LISTENING state; UDP
+/// sockets have no state and are always included.
+pub(crate) fn collect_sockets(
+    listening_only: bool,
+) -> Result<(Vec<SocketEntry>, HashMap<u32, ProcessDetails>), ToolError> {
+    let output = run_tool("netstat", &["-ano"])?;
+    if !output.status.success() {
+        return Err(ToolError::Failed {
+            tool: "netstat",
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut sockets = parse_netstat(&stdout);
+    if listening_only {
+        sockets.retain(|s| s.state.is_empty() || s.state == "LISTEN");
+    }
+
+    let mut names: HashMap<u32, String> = HashMap::new();
+    let details_map = collect_process_details(sockets.iter().map(|s| s.pid), &mut names)?;
+    for socket in &mut sockets {
+        socket.process_name = names.get(&socket.pid).cloned().unwrap_or_default();
+    }
+
+    Ok((sockets, details_map))
+}
+
+/// Parses `netstat -ano` rows such as
+/// `TCP    0.0.0.0:135    0.0.0.0:0    LISTENING    1234` and
+/// `UDP    [::]:5353      *:*                       5678`.
+fn parse_netstat(stdout: &str) -> Vec<SocketEntry> {
+    let mut sockets = Vec::new();
+
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (proto, local, state, pid) = match parts.as_slice() {
+            ["TCP", local, _remote, state, pid] => ("TCP", *local, *state, *pid),
+            ["UDP", local, _remote, pid] => ("UDP", *local, "", *pid),
+            _ => continue,
+        };
+
+        let Ok(pid) = pid.parse::<u32>() else {
+            continue;
+        };
+        let protocol = if local.starts_with('[') {
+            format!("{}6", proto)
+        } else {
+            proto.to_string()
+        };
+        // Match the state names lsof reports so the frontend sees one vocabulary
+        let state = match state {
+            "LISTENING" => "LISTEN".to_string(),
+            other => other.to_string(),
+        };
+
+        if let Some((bind_address, port)) = split_address(local) {
+            sockets.push(SocketEntry {
+                process_name: String::new(),
+                pid,
+                protocol,
+                port,
+                bind_address,
+                state,
+            });
+        }
+    }
+
+    sockets
+}
+
+/// Looks up image name and user for each PID via
+/// `tasklist /V /FI "PID eq <pid>" /FO CSV /NH`, recording image names in `names`.
+///
+/// `tasklist` does not report a full command line or CPU/memory percentages,
+/// so `command` holds the image name and `cpu`/`mem` are left as `None`.
+/// "Mem Usage" is the working set, which stands in for `rss_kb`.
+fn collect_process_details(
+    pids: impl IntoIterator<Item = u32>,
+    names: &mut HashMap<u32, String>,
+) -> Result<HashMap<u32, ProcessDetails>, ToolError> {
+    let mut details_map: HashMap<u32, ProcessDetails> = HashMap::new();
+
+    for pid in pids {
+        if details_map.contains_key(&pid) {
+            continue;
+        }
+        let details = details_map.entry(pid).or_default();
+
+        let filter = format!("PID eq {}", pid);
+        let output = run_tool("tasklist", &["/V", "/FI", &filter, "/FO", "CSV", "/NH"])?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        // Columns: "Image Name","PID","Session Name","Session#","Mem Usage","Status","User Name",...
+        // Rows that don't start with a quote are informational ("INFO: No tasks ...")
+        if let Some(line) = stdout.lines().find(|l| l.starts_with('"')) {
+            let fields: Vec<&str> = line.trim_matches('"').split("\",\"").collect();
+            if let Some(name) = fields.first() {
+                names.insert(pid, name.to_string());
+                details.command = name.to_string();
+            }
+            if let Some(mem_usage) = fields.get(4) {
+                details.rss_kb = parse_mem_usage(mem_usage).unwrap_or(0);
+            }
+            if let Some(user) = fields.get(6) {
+                details.user = user.to_string();
+            }
+        }
+    }
+
+    Ok(details_map)
+}
+
+/// Parses a `tasklist` "Mem Usage" field such as `"12,345 K"` into KiB. The
+/// thousands separator follows the locale, so any non-digit is dropped.
+fn parse_mem_usage(field: &str) -> Option<u64> {
+    let digits: String = field.chars().filter(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// Returns the image name of `pid`, e.g. `"node.exe"`.
+pub(crate) fn image_name(pid: u32) -> Option<String> {
+    let mut names = HashMap::new();
+    collect_process_details([pid], &mut names).ok()?;
+    names.remove(&pid)
+}
+
+/// Returns the user `pid` runs as, e.g. `"DESKTOP-1\\alice"`.
+pub(crate) fn process_user(pid: u32) -> Option<String> {
+    let mut names = HashMap::new();
+    let mut details = collect_process_details([pid], &mut names).ok()?;
+    details.remove(&pid).map(|d| d.user).filter(|user| !user.is_empty())
+}
+
+/// Returns whether `pid` still exists according to `tasklist`.
+pub(crate) fn is_process_alive(pid: u32) -> bool {
+    let filter = format!("PID eq {}", pid);
+    run_tool("tasklist", &["/FI", &filter, "/FO", "CSV", "/NH"])
+        .map(|output| String::from_utf8_lossy(&output.stdout).lines().any(|l| l.starts_with('"')))
+        .unwrap_or(false)
+}
+
+/// Emulates the POSIX signals that have a Windows equivalent: 0 checks for
+/// existence, 15 asks the process to close and 9 forces termination.
+pub(crate) fn deliver_signal(pid: u32, signal: i32) -> Result<(), SignalError> {
+    let pid_str = pid.to_string();
+    let output = match signal {
+        0 if is_process_alive(pid) => return Ok(()),
+        0 => return Err(SignalError::Failed("No such process".to_string())),
+        9 => run_tool("taskkill", &["/F", "/PID", &pid_str])?,
+        15 => run_tool("taskkill", &["/PID", &pid_str])?,
+        _ => {
+            return Err(SignalError::Failed(format!(
+                "Signal {} is not supported on Windows",
+                signal
+            )))
+        }
+    };
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SignalError::Failed(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_netstat_reads_tcp_and_udp_rows() {
+        let stdout = "\
+Active Connections
+
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1032
+  TCP    127.0.0.1:5432         127.0.0.1:61544        ESTABLISHED     4188
+  TCP    [::]:445               [::]:0                 LISTENING       4
+  UDP    0.0.0.0:5353           *:*                                    2260
+  UDP    [::]:5353              *:*                                    2260
+";
+        let sockets: Vec<_> = parse_netstat(stdout)
+            .into_iter()
+            .map(|s| (s.pid, s.protocol, s.bind_address, s.port, s.state))
+            .collect();
+        let expected = [
+            (1032, "TCP", "0.0.0.0", 135, "LISTEN"),
+            (4188, "TCP", "127.0.0.1", 5432, "ESTABLISHED"),
+            (4, "TCP6", "::", 445, "LISTEN"),
+            (2260, "UDP", "0.0.0.0", 5353, ""),
+            (2260, "UDP6", "::", 5353, ""),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|&(pid, protocol, bind_address, port, state)| {
+                (pid, protocol.to_string(), bind_address.to_string(), port, state.to_string())
+            })
+            .collect();
+        assert_eq!(sockets, expected);
+    }
+
+    #[test]
+    fn parse_mem_usage_ignores_the_thousands_separator() {
+        assert_eq!(parse_mem_usage("12,345 K"), Some(12_345));
+        assert_eq!(parse_mem_usage("12.345 K"), Some(12_345));
+        assert_eq!(parse_mem_usage("836 K"), Some(836));
+        assert_eq!(parse_mem_usage("N/A"), None);
+    }
+}